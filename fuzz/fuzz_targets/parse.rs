@@ -0,0 +1,13 @@
+#![no_main]
+
+use calculator::calcparse::ast;
+use calculator::calcparse::parser::Parser;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|expr: &str| {
+    if let Ok(mut parser) = Parser::new(expr, None) {
+        if let Ok(ast) = parser.parse() {
+            let _ = ast::eval(ast);
+        }
+    }
+});
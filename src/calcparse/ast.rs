@@ -1,5 +1,24 @@
-use statrs::function::gamma::gamma;
-use std::error;
+use super::constants;
+use super::context::{Context, FloatPolicy};
+use super::derivative::derivative;
+use super::distribution::{self, Distribution, DistributionFunction};
+use super::error::EvalError;
+use super::finance::{self, Financial};
+use super::matrix::{self, Matrix};
+use super::number_theory;
+use super::numeric;
+use super::polynomial;
+use super::programmer::IntegerMode;
+use super::special;
+use super::statistics;
+use super::token::NativeFunction;
+use super::value::{approx_eq, Value};
+use num_complex::Complex64;
+use rand::rngs::StdRng;
+use rand::Rng;
+use statrs::function::erf::{erf, erf_inv, erfc};
+use statrs::function::gamma::digamma;
+use std::cmp::Ordering;
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum Node {
@@ -7,10 +26,47 @@ pub enum Node {
     Subtract(Box<Node>, Box<Node>),
     Multiply(Box<Node>, Box<Node>),
     Divide(Box<Node>, Box<Node>),
+    /// `a \ b`, the solution of `a x = b`.
+    LeftDivide(Box<Node>, Box<Node>),
     Modulo(Box<Node>, Box<Node>),
+    IntegerDivide(Box<Node>, Box<Node>),
+    BitAnd(Box<Node>, Box<Node>),
+    BitOr(Box<Node>, Box<Node>),
+    BitXor(Box<Node>, Box<Node>),
+    BitNot(Box<Node>),
+    ShiftLeft(Box<Node>, Box<Node>),
+    ShiftRight(Box<Node>, Box<Node>),
+    Less(Box<Node>, Box<Node>),
+    LessEqual(Box<Node>, Box<Node>),
+    Greater(Box<Node>, Box<Node>),
+    GreaterEqual(Box<Node>, Box<Node>),
+    Equal(Box<Node>, Box<Node>),
+    NotEqual(Box<Node>, Box<Node>),
+    And(Box<Node>, Box<Node>),
+    Or(Box<Node>, Box<Node>),
+    Not(Box<Node>),
+    If(Box<Node>, Box<Node>, Box<Node>),
+    Piecewise(Vec<Node>),
+    /// Index variable, first and last index, then the expression summed over.
+    Sum(String, Box<Node>, Box<Node>, Box<Node>),
+    Product(String, Box<Node>, Box<Node>, Box<Node>),
+    /// Variable of integration, lower and upper bound, then the integrand.
+    Integrate(String, Box<Node>, Box<Node>, Box<Node>),
+    /// Variable, initial guess, then the function whose root is searched for.
+    Solve(String, Box<Node>, Box<Node>),
+    /// Variable, both ends of a bracket, then the function whose root is searched for.
+    Root(String, Box<Node>, Box<Node>, Box<Node>),
+    /// Variable, then the polynomial whose roots are searched for.
+    Roots(String, Box<Node>),
+    /// Variable, the point of interest, then the function.
+    Derivative(String, Box<Node>, Box<Node>),
+    Limit(String, Box<Node>, Box<Node>),
+    LimitLeft(String, Box<Node>, Box<Node>),
+    LimitRight(String, Box<Node>, Box<Node>),
     Caret(Box<Node>, Box<Node>),
     Negative(Box<Node>),
     Factorial(Box<Node>),
+    DoubleFactorial(Box<Node>),
     Abs(Box<Node>),
     Floor(Box<Node>),
     Ceil(Box<Node>),
@@ -37,88 +93,1381 @@ pub enum Node {
     Exp2(Box<Node>),
     Truncate(Box<Node>),
     Sign(Box<Node>),
+    Gamma(Box<Node>),
+    LnGamma(Box<Node>),
+    Digamma(Box<Node>),
+    Erf(Box<Node>),
+    Erfc(Box<Node>),
+    ErfInv(Box<Node>),
+    Zeta(Box<Node>),
+    LambertW(Box<Node>),
+    Beta(Box<Node>, Box<Node>),
+    /// Order, then the point the Bessel function is evaluated at.
+    BesselJ(Box<Node>, Box<Node>),
+    BesselY(Box<Node>, Box<Node>),
+    BesselI(Box<Node>, Box<Node>),
+    BesselK(Box<Node>, Box<Node>),
+    /// Regularized incomplete gamma functions, the parameter first.
+    GammaInc(Box<Node>, Box<Node>),
+    GammaIncC(Box<Node>, Box<Node>),
+    /// Regularized incomplete beta function, both parameters first.
+    BetaInc(Box<Node>, Box<Node>, Box<Node>),
+    /// `nCr(n, k)`.
+    Binomial(Box<Node>, Box<Node>),
+    /// `nPr(n, k)`.
+    Permutations(Box<Node>, Box<Node>),
+    Gcd(Vec<Node>),
+    Lcm(Vec<Node>),
+    IsPrime(Box<Node>),
+    NextPrime(Box<Node>),
+    Factor(Box<Node>),
+    Totient(Box<Node>),
+    /// Base, exponent and modulus.
+    ModPow(Box<Node>, Box<Node>, Box<Node>),
+    ModInv(Box<Node>, Box<Node>),
+    Fibonacci(Box<Node>),
+    Subfactorial(Box<Node>),
+    /// A random number between 0 included and 1 excluded.
+    Rand,
+    /// A random integer between both bounds included.
+    RandInt(Box<Node>, Box<Node>),
+    /// A random element of a list.
+    Choice(Box<Node>),
     Min(Vec<Node>),
     Max(Vec<Node>),
+    /// Sum of the given values, `sum` called with anything else than the arguments of a series.
+    Total(Vec<Node>),
+    Mean(Vec<Node>),
+    Median(Vec<Node>),
+    Mode(Vec<Node>),
+    Variance(Vec<Node>),
+    PopulationVariance(Vec<Node>),
+    StandardDeviation(Vec<Node>),
+    PopulationStandardDeviation(Vec<Node>),
+    /// Values, then the order of the quantile as the last argument.
+    Quantile(Vec<Node>),
+    GeometricMean(Vec<Node>),
+    HarmonicMean(Vec<Node>),
+    Skewness(Vec<Node>),
+    Kurtosis(Vec<Node>),
+    /// Point the distribution is evaluated at, then its parameters.
+    Distribution(Distribution, DistributionFunction, Vec<Node>),
+    /// A random number following a distribution, given its parameters.
+    Sample(Distribution, Vec<Node>),
+    /// Time value of money functions, whose arguments may be lists of cash flows.
+    Financial(Financial, Vec<Node>),
     Log(Box<Node>, Box<Node>),
     Number(f64),
+    Boolean(bool),
+    Complex(Complex64),
+    List(Vec<Node>),
+    /// A list, then the position of the element taken from it, starting at 0.
+    Index(Box<Node>, Box<Node>),
+    /// Every integer from the first bound up to the second.
+    Range(Box<Node>, Box<Node>),
+    Len(Box<Node>),
+    Transpose(Box<Node>),
+    Determinant(Box<Node>),
+    Inverse(Box<Node>),
+    /// Identity matrix of the given size.
+    Identity(Box<Node>),
+    Trace(Box<Node>),
+    Rank(Box<Node>),
+    Eigenvalues(Box<Node>),
+    Variable(String),
+    /// What couldn't be parsed, in the tree given by `Parser::parse_with_diagnostics`.
+    Error,
 }
 
-pub fn eval(expr: Node) -> Result<f64, Box<dyn error::Error>> {
-    use self::Node::*;
-    match expr {
-        Number(i) => Ok(i),
-        Add(expr1, expr2) => Ok(eval(*expr1)? + eval(*expr2)?),
-        Subtract(expr1, expr2) => Ok(eval(*expr1)? - eval(*expr2)?),
-        Multiply(expr1, expr2) => Ok(eval(*expr1)? * eval(*expr2)?),
-        Divide(expr1, expr2) => Ok(eval(*expr1)? / eval(*expr2)?),
-        Modulo(expr1, expr2) => Ok(eval(*expr1)? % eval(*expr2)?),
-        Negative(expr1) => Ok(-(eval(*expr1)?)),
-        Caret(expr1, expr2) => Ok(eval(*expr1)?.powf(eval(*expr2)?)),
-        Pow(expr1, expr2) => Ok(eval(*expr1)?.powf(eval(*expr2)?)),
-        Factorial(sub_expr) => {
-            let sub_result = eval(*sub_expr)?;
-            if sub_result >= 0.0 {
-                if (sub_result % 1.0) > 0.0 {
-                    Ok(gamma(sub_result + 1.0))
+impl From<Value> for Node {
+    fn from(value: Value) -> Self {
+        match value {
+            Value::Number(number) => Node::Number(number),
+            Value::Boolean(boolean) => Node::Boolean(boolean),
+            Value::Complex(complex) => Node::Complex(complex),
+            Value::List(values) => Node::List(values.into_iter().map(Node::from).collect()),
+            Value::Matrix(matrix) => Node::List(
+                (0..matrix.rows())
+                    .map(|i| Node::List(matrix.row(i).iter().map(|x| Node::Number(*x)).collect()))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+impl Node {
+    /// The sub-expressions a node needs evaluated before it can be computed, in evaluation order.
+    /// Expressions evaluated by the node itself, such as the body of a sum, aren't part of them.
+    pub fn children(&self) -> Vec<&Node> {
+        use self::Node::*;
+        match self {
+            Number(_) | Boolean(_) | Complex(_) | Variable(_) | Rand | Error | Roots(..) => {
+                Vec::new()
+            }
+            Add(expr1, expr2)
+            | Subtract(expr1, expr2)
+            | Multiply(expr1, expr2)
+            | Divide(expr1, expr2)
+            | LeftDivide(expr1, expr2)
+            | Modulo(expr1, expr2)
+            | IntegerDivide(expr1, expr2)
+            | BitAnd(expr1, expr2)
+            | BitOr(expr1, expr2)
+            | BitXor(expr1, expr2)
+            | ShiftLeft(expr1, expr2)
+            | ShiftRight(expr1, expr2)
+            | Less(expr1, expr2)
+            | LessEqual(expr1, expr2)
+            | Greater(expr1, expr2)
+            | GreaterEqual(expr1, expr2)
+            | Equal(expr1, expr2)
+            | NotEqual(expr1, expr2)
+            | And(expr1, expr2)
+            | Or(expr1, expr2)
+            | Caret(expr1, expr2)
+            | Pow(expr1, expr2)
+            | Log(expr1, expr2)
+            | Atan2(expr1, expr2)
+            | Index(expr1, expr2)
+            | Range(expr1, expr2)
+            | Beta(expr1, expr2)
+            | BesselJ(expr1, expr2)
+            | BesselY(expr1, expr2)
+            | BesselI(expr1, expr2)
+            | BesselK(expr1, expr2)
+            | GammaInc(expr1, expr2)
+            | GammaIncC(expr1, expr2)
+            | Binomial(expr1, expr2)
+            | Permutations(expr1, expr2)
+            | ModInv(expr1, expr2)
+            | RandInt(expr1, expr2)
+            | Sum(_, expr1, expr2, _)
+            | Product(_, expr1, expr2, _)
+            | Integrate(_, expr1, expr2, _)
+            | Root(_, expr1, expr2, _) => vec![expr1, expr2],
+            BitNot(sub_expr)
+            | Not(sub_expr)
+            | Negative(sub_expr)
+            | Factorial(sub_expr)
+            | DoubleFactorial(sub_expr)
+            | IsPrime(sub_expr)
+            | NextPrime(sub_expr)
+            | Factor(sub_expr)
+            | Totient(sub_expr)
+            | Fibonacci(sub_expr)
+            | Subfactorial(sub_expr)
+            | Choice(sub_expr)
+            | Abs(sub_expr)
+            | Floor(sub_expr)
+            | Ceil(sub_expr)
+            | Round(sub_expr)
+            | Sin(sub_expr)
+            | Cos(sub_expr)
+            | Tan(sub_expr)
+            | Sinh(sub_expr)
+            | Cosh(sub_expr)
+            | Tanh(sub_expr)
+            | Arsinh(sub_expr)
+            | Arcosh(sub_expr)
+            | Artanh(sub_expr)
+            | Asin(sub_expr)
+            | Acos(sub_expr)
+            | Atan(sub_expr)
+            | Sqrt(sub_expr)
+            | Pow2(sub_expr)
+            | Pow3(sub_expr)
+            | Ln(sub_expr)
+            | Exp(sub_expr)
+            | Exp2(sub_expr)
+            | Truncate(sub_expr)
+            | Sign(sub_expr)
+            | Len(sub_expr)
+            | Transpose(sub_expr)
+            | Determinant(sub_expr)
+            | Inverse(sub_expr)
+            | Identity(sub_expr)
+            | Trace(sub_expr)
+            | Rank(sub_expr)
+            | Eigenvalues(sub_expr)
+            | Gamma(sub_expr)
+            | LnGamma(sub_expr)
+            | Digamma(sub_expr)
+            | Erf(sub_expr)
+            | Erfc(sub_expr)
+            | ErfInv(sub_expr)
+            | Zeta(sub_expr)
+            | LambertW(sub_expr) => vec![sub_expr],
+            Solve(_, at, _)
+            | Derivative(_, at, _)
+            | Limit(_, at, _)
+            | LimitLeft(_, at, _)
+            | LimitRight(_, at, _) => vec![at],
+            If(condition, then_expr, else_expr) => vec![condition, then_expr, else_expr],
+            BetaInc(a, b, x) | ModPow(a, b, x) => vec![a, b, x],
+            Min(args)
+            | Max(args)
+            | Piecewise(args)
+            | List(args)
+            | Total(args)
+            | Mean(args)
+            | Median(args)
+            | Mode(args)
+            | Variance(args)
+            | PopulationVariance(args)
+            | StandardDeviation(args)
+            | PopulationStandardDeviation(args)
+            | Quantile(args)
+            | GeometricMean(args)
+            | HarmonicMean(args)
+            | Skewness(args)
+            | Kurtosis(args)
+            | Gcd(args)
+            | Lcm(args)
+            | Distribution(_, _, args)
+            | Sample(_, args)
+            | Financial(_, args) => args.iter().collect(),
+        }
+    }
+    /// Every sub-expression owned by the node.
+    fn children_mut(&mut self) -> Vec<&mut Node> {
+        use self::Node::*;
+        match self {
+            Number(_) | Boolean(_) | Complex(_) | Variable(_) | Rand | Error => Vec::new(),
+            Add(expr1, expr2)
+            | Subtract(expr1, expr2)
+            | Multiply(expr1, expr2)
+            | Divide(expr1, expr2)
+            | LeftDivide(expr1, expr2)
+            | Modulo(expr1, expr2)
+            | IntegerDivide(expr1, expr2)
+            | BitAnd(expr1, expr2)
+            | BitOr(expr1, expr2)
+            | BitXor(expr1, expr2)
+            | ShiftLeft(expr1, expr2)
+            | ShiftRight(expr1, expr2)
+            | Less(expr1, expr2)
+            | LessEqual(expr1, expr2)
+            | Greater(expr1, expr2)
+            | GreaterEqual(expr1, expr2)
+            | Equal(expr1, expr2)
+            | NotEqual(expr1, expr2)
+            | And(expr1, expr2)
+            | Or(expr1, expr2)
+            | Caret(expr1, expr2)
+            | Pow(expr1, expr2)
+            | Log(expr1, expr2)
+            | Atan2(expr1, expr2)
+            | Index(expr1, expr2)
+            | Range(expr1, expr2)
+            | Beta(expr1, expr2)
+            | BesselJ(expr1, expr2)
+            | BesselY(expr1, expr2)
+            | BesselI(expr1, expr2)
+            | BesselK(expr1, expr2)
+            | GammaInc(expr1, expr2)
+            | GammaIncC(expr1, expr2)
+            | Binomial(expr1, expr2)
+            | Permutations(expr1, expr2)
+            | ModInv(expr1, expr2)
+            | RandInt(expr1, expr2) => vec![expr1, expr2],
+            Sum(_, from, to, body)
+            | Product(_, from, to, body)
+            | Integrate(_, from, to, body)
+            | Root(_, from, to, body) => vec![from, to, body],
+            Solve(_, at, body)
+            | Derivative(_, at, body)
+            | Limit(_, at, body)
+            | LimitLeft(_, at, body)
+            | LimitRight(_, at, body) => vec![at, body],
+            Roots(_, body) => vec![body],
+            BitNot(sub_expr)
+            | Not(sub_expr)
+            | Negative(sub_expr)
+            | Factorial(sub_expr)
+            | DoubleFactorial(sub_expr)
+            | IsPrime(sub_expr)
+            | NextPrime(sub_expr)
+            | Factor(sub_expr)
+            | Totient(sub_expr)
+            | Fibonacci(sub_expr)
+            | Subfactorial(sub_expr)
+            | Choice(sub_expr)
+            | Abs(sub_expr)
+            | Floor(sub_expr)
+            | Ceil(sub_expr)
+            | Round(sub_expr)
+            | Sin(sub_expr)
+            | Cos(sub_expr)
+            | Tan(sub_expr)
+            | Sinh(sub_expr)
+            | Cosh(sub_expr)
+            | Tanh(sub_expr)
+            | Arsinh(sub_expr)
+            | Arcosh(sub_expr)
+            | Artanh(sub_expr)
+            | Asin(sub_expr)
+            | Acos(sub_expr)
+            | Atan(sub_expr)
+            | Sqrt(sub_expr)
+            | Pow2(sub_expr)
+            | Pow3(sub_expr)
+            | Ln(sub_expr)
+            | Exp(sub_expr)
+            | Exp2(sub_expr)
+            | Truncate(sub_expr)
+            | Sign(sub_expr)
+            | Len(sub_expr)
+            | Transpose(sub_expr)
+            | Determinant(sub_expr)
+            | Inverse(sub_expr)
+            | Identity(sub_expr)
+            | Trace(sub_expr)
+            | Rank(sub_expr)
+            | Eigenvalues(sub_expr)
+            | Gamma(sub_expr)
+            | LnGamma(sub_expr)
+            | Digamma(sub_expr)
+            | Erf(sub_expr)
+            | Erfc(sub_expr)
+            | ErfInv(sub_expr)
+            | Zeta(sub_expr)
+            | LambertW(sub_expr) => vec![sub_expr],
+            If(condition, then_expr, else_expr) => vec![condition, then_expr, else_expr],
+            BetaInc(a, b, x) | ModPow(a, b, x) => vec![a, b, x],
+            Min(args)
+            | Max(args)
+            | Piecewise(args)
+            | List(args)
+            | Total(args)
+            | Mean(args)
+            | Median(args)
+            | Mode(args)
+            | Variance(args)
+            | PopulationVariance(args)
+            | StandardDeviation(args)
+            | PopulationStandardDeviation(args)
+            | Quantile(args)
+            | GeometricMean(args)
+            | HarmonicMean(args)
+            | Skewness(args)
+            | Kurtosis(args)
+            | Gcd(args)
+            | Lcm(args)
+            | Distribution(_, _, args)
+            | Sample(_, args)
+            | Financial(_, args) => args.iter_mut().collect(),
+        }
+    }
+    /// How the operation is referred to in messages, the name of the function or a description
+    /// of the operator.
+    pub fn name(&self) -> &'static str {
+        use self::Node::*;
+        match self {
+            Add(..) => "addition",
+            Subtract(..) => "subtraction",
+            Multiply(..) => "multiplication",
+            Divide(..) => "division",
+            LeftDivide(..) => "left division",
+            Modulo(..) => "modulo",
+            IntegerDivide(..) => "integer division",
+            BitAnd(..) => "bitwise and",
+            BitOr(..) => "bitwise or",
+            BitXor(..) => "xor",
+            BitNot(_) => "bitwise not",
+            ShiftLeft(..) => "left shift",
+            ShiftRight(..) => "right shift",
+            Less(..) | LessEqual(..) | Greater(..) | GreaterEqual(..) => "comparison",
+            Equal(..) | NotEqual(..) => "equality",
+            And(..) => "and",
+            Or(..) => "or",
+            Not(_) => "not",
+            If(..) => "if",
+            Piecewise(_) => "piecewise",
+            Sum(..) => "sum",
+            Product(..) => "prod",
+            Integrate(..) => "integrate",
+            Solve(..) => "solve",
+            Root(..) => "root",
+            Roots(..) => "roots",
+            Derivative(..) => "deriv",
+            Limit(..) => "limit",
+            LimitLeft(..) => "limit_left",
+            LimitRight(..) => "limit_right",
+            Caret(..) => "power",
+            Negative(_) => "negation",
+            Factorial(_) => "factorial",
+            DoubleFactorial(_) => "double factorial",
+            Abs(_) => "abs",
+            Floor(_) => "floor",
+            Ceil(_) => "ceil",
+            Round(_) => "round",
+            Sin(_) => "sin",
+            Cos(_) => "cos",
+            Tan(_) => "tan",
+            Sinh(_) => "sinh",
+            Cosh(_) => "cosh",
+            Tanh(_) => "tanh",
+            Arsinh(_) => "asinh",
+            Arcosh(_) => "acosh",
+            Artanh(_) => "atanh",
+            Asin(_) => "asin",
+            Acos(_) => "acos",
+            Atan(_) => "atan",
+            Atan2(..) => "atan2",
+            Sqrt(_) => "sqrt",
+            Pow(..) => "pow",
+            Pow2(_) | Pow3(_) => "power",
+            Ln(_) => "ln",
+            Exp(_) => "exp",
+            Exp2(_) => "exp2",
+            Truncate(_) => "trunc",
+            Sign(_) => "sign",
+            Gamma(_) => "gamma",
+            LnGamma(_) => "lgamma",
+            Digamma(_) => "digamma",
+            Erf(_) => "erf",
+            Erfc(_) => "erfc",
+            ErfInv(_) => "erfinv",
+            Zeta(_) => "zeta",
+            LambertW(_) => "lambertw",
+            Beta(..) => "beta",
+            BesselJ(..) => "besselj",
+            BesselY(..) => "bessely",
+            BesselI(..) => "besseli",
+            BesselK(..) => "besselk",
+            GammaInc(..) => "gammainc",
+            GammaIncC(..) => "gammaincc",
+            BetaInc(..) => "betainc",
+            Binomial(..) => "nCr",
+            Permutations(..) => "nPr",
+            Gcd(_) => "gcd",
+            Lcm(_) => "lcm",
+            IsPrime(_) => "isprime",
+            NextPrime(_) => "nextprime",
+            Factor(_) => "factor",
+            Totient(_) => "totient",
+            ModPow(..) => "mod_pow",
+            ModInv(..) => "mod_inv",
+            Fibonacci(_) => "fib",
+            Subfactorial(_) => "subfactorial",
+            Rand => "rand",
+            RandInt(..) => "randint",
+            Choice(_) => "choice",
+            Min(_) => "min",
+            Max(_) => "max",
+            Total(_) => "sum",
+            Mean(_) => "mean",
+            Median(_) => "median",
+            Mode(_) => "mode",
+            Variance(_) => "var",
+            PopulationVariance(_) => "varp",
+            StandardDeviation(_) => "stdev",
+            PopulationStandardDeviation(_) => "stdevp",
+            Quantile(_) => "quantile",
+            GeometricMean(_) => "geomean",
+            HarmonicMean(_) => "harmean",
+            Skewness(_) => "skewness",
+            Kurtosis(_) => "kurtosis",
+            Distribution(distribution, function, _) => {
+                NativeFunction::Distribution(*distribution, *function).name()
+            }
+            Sample(distribution, _) => NativeFunction::Sample(*distribution).name(),
+            Financial(function, _) => function.name(),
+            Log(..) => "log",
+            Number(_) | Boolean(_) | Complex(_) => "literal",
+            List(_) => "list",
+            Index(..) => "indexing",
+            Range(..) => "range",
+            Len(_) => "len",
+            Transpose(_) => "transpose",
+            Determinant(_) => "det",
+            Inverse(_) => "inv",
+            Identity(_) => "identity",
+            Trace(_) => "trace",
+            Rank(_) => "rank",
+            Eigenvalues(_) => "eig",
+            Variable(_) => "variable",
+            Error => "syntax error",
+        }
+    }
+    /// The variable bound by a node such as `sum` or `integrate`, along with the expression it's
+    /// bound in.
+    pub fn binding(&self) -> Option<(&str, &Node)> {
+        use self::Node::*;
+        match self {
+            Sum(variable, _, _, body)
+            | Product(variable, _, _, body)
+            | Integrate(variable, _, _, body)
+            | Root(variable, _, _, body)
+            | Solve(variable, _, body)
+            | Derivative(variable, _, body)
+            | Limit(variable, _, body)
+            | LimitLeft(variable, _, body)
+            | LimitRight(variable, _, body)
+            | Roots(variable, body) => Some((variable, body)),
+            _ => None,
+        }
+    }
+    /// Whether `variable` appears in the node, other than where a nested `sum` or the like
+    /// binds a variable with the same name.
+    pub fn depends_on(&self, variable: &str) -> bool {
+        let mut pending = vec![self];
+        while let Some(node) = pending.pop() {
+            if matches!(node, Node::Variable(name) if name == variable) {
+                return true;
+            }
+            pending.extend(node.children());
+            if let Some((name, body)) = node.binding() {
+                if name != variable {
+                    pending.push(body);
+                }
+            }
+        }
+        false
+    }
+    /// Variables appearing in the node that aren't bound by it, in order of appearance. Names of
+    /// constants are included, since a variable shadows the constant of the same name.
+    pub fn free_variables(&self) -> Vec<String> {
+        let mut variables = Vec::new();
+        let mut pending = vec![(self, Vec::new())];
+        while let Some((node, bound)) = pending.pop() {
+            if let Node::Variable(name) = node {
+                if !bound.contains(&name.as_str()) && !variables.contains(name) {
+                    variables.push(name.clone());
+                }
+            }
+            if let Some((name, body)) = node.binding() {
+                let mut bound = bound.clone();
+                bound.push(name);
+                pending.push((body, bound));
+            }
+            for child in node.children().into_iter().rev() {
+                pending.push((child, bound.clone()));
+            }
+        }
+        variables
+    }
+    /// The free variables that aren't named after a constant.
+    pub fn unknowns(&self) -> Vec<String> {
+        let mut variables = self.free_variables();
+        variables.retain(|name| constants::find(name).is_none());
+        variables
+    }
+}
+
+/// Dropping is done with an explicit stack too, the derived drop would recurse once per level
+/// and overflow on the long formulas the evaluator is able to handle.
+impl Drop for Node {
+    fn drop(&mut self) {
+        let mut pending = Vec::new();
+        for child in self.children_mut() {
+            if !child.children().is_empty() {
+                pending.push(std::mem::replace(child, Node::Number(0.0)));
+            }
+        }
+        while let Some(mut node) = pending.pop() {
+            for child in node.children_mut() {
+                if !child.children().is_empty() {
+                    pending.push(std::mem::replace(child, Node::Number(0.0)));
+                }
+            }
+        }
+    }
+}
+
+pub fn eval(expr: Node) -> Result<Value, EvalError> {
+    eval_with(expr, &mut Context::default())
+}
+
+pub fn eval_with(expr: Node, context: &mut Context) -> Result<Value, EvalError> {
+    context.estimated_error = None;
+    eval_node(&expr, context)
+}
+
+enum Step<'a> {
+    Visit(&'a Node),
+    Apply(&'a Node, usize),
+    /// Pick a branch of an `If` or a `Piecewise` once the condition at this index is known.
+    Select(&'a Node, usize),
+}
+
+/// Evaluate `expr`, numerical methods such as `integrate` calling this again for their
+/// expression, which is refused beyond `MAX_NESTED_EVALUATIONS` levels.
+fn eval_node(expr: &Node, context: &mut Context) -> Result<Value, EvalError> {
+    context.enter_evaluation()?;
+    let value = eval_tree(expr, context);
+    context.leave_evaluation();
+    value
+}
+
+/// Evaluate with an explicit stack rather than recursion, so that the depth of the tree
+/// is only limited by the available memory.
+fn eval_tree(expr: &Node, context: &mut Context) -> Result<Value, EvalError> {
+    let mut steps = vec![Step::Visit(expr)];
+    let mut values = Vec::new();
+    while let Some(step) = steps.pop() {
+        match step {
+            // Only the condition is evaluated here, the untaken branches never are. The right side
+            // of `and` and `or` is only evaluated when the left one doesn't decide.
+            Step::Visit(
+                node @ (Node::If(..) | Node::Piecewise(_) | Node::And(..) | Node::Or(..)),
+            ) => {
+                let children = node.children();
+                if children.len() > 1 {
+                    steps.push(Step::Select(node, 0));
+                }
+                steps.push(Step::Visit(children[0]));
+            }
+            Step::Visit(node) => {
+                let children = node.children();
+                steps.push(Step::Apply(node, children.len()));
+                steps.extend(children.into_iter().rev().map(Step::Visit));
+            }
+            Step::Apply(node, arity) => {
+                let args = values.split_off(values.len() - arity);
+                values.push(apply(node, &args, context)?);
+            }
+            Step::Select(node @ (Node::And(..) | Node::Or(..)), _) => {
+                // A left side that isn't a decisive boolean (e.g. a list) goes through `apply`.
+                let decisive = Value::Boolean(matches!(node, Node::Or(..)));
+                if values.last() != Some(&decisive) {
+                    steps.push(Step::Apply(node, 2));
+                    steps.push(Step::Visit(node.children()[1]));
+                }
+            }
+            Step::Select(node, index) => {
+                let children = node.children();
+                let condition = values.pop().unwrap_or(Value::Boolean(false));
+                if condition.as_boolean()? {
+                    steps.push(Step::Visit(children[index + 1]));
+                } else if index + 3 >= children.len() {
+                    steps.push(Step::Visit(children[children.len() - 1]));
                 } else {
-                    let mut factorial_result = 1.0;
-                    for i in 2..=(sub_result as usize) {
-                        factorial_result *= i as f64;
-                    }
-                    Ok(factorial_result)
+                    steps.push(Step::Select(node, index + 2));
+                    steps.push(Step::Visit(children[index + 2]));
                 }
-            } else if (sub_result % 1.0) == 0.0 {
-                Ok(f64::NAN)
+            }
+        }
+    }
+    Ok(values.pop().unwrap_or(Value::Number(0.0)))
+}
+
+/// Evaluate `function` with `variable` bound to `x`, as needed by numerical methods.
+fn eval_at(
+    function: &Node,
+    variable: &str,
+    x: f64,
+    context: &mut Context,
+) -> Result<f64, EvalError> {
+    context.bind(variable, Value::Number(x));
+    let y = eval_node(function, context);
+    context.unbind();
+    y?.as_number()
+}
+
+/// Limit of `f` at `a`, from the side `node` asks for.
+fn limit(
+    node: &Node,
+    f: &mut numeric::Function,
+    a: f64,
+    precision: f64,
+) -> Result<numeric::Estimate, EvalError> {
+    use self::Node::*;
+    let estimate = match node {
+        LimitLeft(..) => numeric::limit(f, a, -1.0, precision)?,
+        LimitRight(..) => numeric::limit(f, a, 1.0, precision)?,
+        _ if a.is_infinite() => numeric::limit(f, a, 1.0, precision)?,
+        _ => {
+            let left = numeric::limit(f, a, -1.0, precision)?;
+            let right = numeric::limit(f, a, 1.0, precision)?;
+            let tolerance = (left.error + right.error).max(precision.sqrt());
+            if !approx_eq(left.value, right.value, tolerance) {
+                // The one-sided limits differ.
+                return Err(EvalError::Domain {
+                    function: "limit",
+                    arguments: vec![a],
+                });
+            }
+            numeric::Estimate {
+                value: 0.5 * (left.value + right.value),
+                error: left.error.max(right.error),
+            }
+        }
+    };
+    Ok(estimate)
+}
+
+/// Run a numerical method and add its error estimate to the context's. The estimates of the
+/// methods nested in its function, such as the inner integral of a double integral, are left
+/// out: they'd be summed over every sample, which bounds nothing.
+fn outermost_estimate(
+    context: &mut Context,
+    method: impl FnOnce(&mut Context) -> Result<numeric::Estimate, EvalError>,
+) -> Result<f64, EvalError> {
+    let outer_error = context.estimated_error.take();
+    let estimate = method(context);
+    context.estimated_error = outer_error;
+    let estimate = estimate?;
+    *context.estimated_error.get_or_insert(0.0) += estimate.error;
+    Ok(estimate.value)
+}
+
+/// Upper bound on the number of terms of a sum or a product.
+pub const MAX_SERIES_TERMS: u64 = 10_000_000;
+
+/// Upper bound on the number of elements of a range.
+pub const MAX_RANGE_LENGTH: u64 = 1_000_000;
+
+type IntegerOperation = fn(&IntegerMode, i128, i128) -> Result<i128, EvalError>;
+
+/// Apply an integer operation using the context's integer mode, or 64-bit signed integers outside of programmer mode.
+fn integer_op(
+    context: &Context,
+    expr1: f64,
+    expr2: f64,
+    operation: IntegerOperation,
+) -> Result<f64, EvalError> {
+    let mode = context.integer_mode.unwrap_or_default();
+    Ok(operation(&mode, mode.integer_from(expr1)?, mode.integer_from(expr2)?)? as f64)
+}
+
+/// Same as `integer_op`, but falls back to the floating point `operation` outside of programmer mode.
+fn arithmetic_op(
+    context: &Context,
+    expr1: f64,
+    expr2: f64,
+    integer_operation: IntegerOperation,
+    operation: fn(f64, f64) -> f64,
+) -> Result<f64, EvalError> {
+    if context.integer_mode.is_some() {
+        integer_op(context, expr1, expr2, integer_operation)
+    } else {
+        Ok(operation(expr1, expr2))
+    }
+}
+
+fn factorial(sub_result: f64) -> f64 {
+    if sub_result >= 0.0 {
+        if (sub_result % 1.0) > 0.0 {
+            special::gamma(sub_result + 1.0)
+        } else if sub_result > 170.0 {
+            // 171! is already beyond f64::MAX, don't loop up to a huge integer.
+            f64::INFINITY
+        } else {
+            let mut factorial_result = 1.0;
+            for i in 2..=(sub_result as usize) {
+                factorial_result *= i as f64;
+            }
+            factorial_result
+        }
+    } else if (sub_result % 1.0) == 0.0 {
+        f64::NAN
+    } else {
+        special::gamma(sub_result + 1.0)
+    }
+}
+
+/// A uniformly distributed integer from `low` to `high`, both included.
+fn random_integer(low: f64, high: f64, rng: &mut StdRng) -> Result<f64, EvalError> {
+    let is_integer = |x: f64| x.fract() == 0.0 && x.abs() <= number_theory::MAX_INTEGER;
+    if !is_integer(low) || !is_integer(high) || low > high {
+        return Err(EvalError::Domain {
+            function: "randint",
+            arguments: vec![low, high],
+        });
+    }
+    Ok(rng.gen_range(low as i64..=high as i64) as f64)
+}
+
+/// Roots as values, real ones first, then complex ones, both sorted by their real part.
+fn root_values(roots: Vec<Complex64>) -> Value {
+    let mut roots: Vec<Value> = roots
+        .into_iter()
+        .map(|root| {
+            if root.im.abs() <= 1e-12 * root.re.abs().max(1.0) {
+                Value::Number(root.re)
             } else {
-                Ok(gamma(sub_result + 1.0))
-            }
-        }
-        Abs(sub_expr) => Ok(eval(*sub_expr)?.abs()),
-        Floor(sub_expr) => Ok(eval(*sub_expr)?.floor()),
-        Ceil(sub_expr) => Ok(eval(*sub_expr)?.ceil()),
-        Round(sub_expr) => Ok(eval(*sub_expr)?.round()),
-        Sin(sub_expr) => Ok(eval(*sub_expr)?.sin()),
-        Cos(sub_expr) => Ok(eval(*sub_expr)?.cos()),
-        Tan(sub_expr) => Ok(eval(*sub_expr)?.tan()),
-        Sinh(sub_expr) => Ok(eval(*sub_expr)?.sinh()),
-        Cosh(sub_expr) => Ok(eval(*sub_expr)?.cosh()),
-        Tanh(sub_expr) => Ok(eval(*sub_expr)?.tanh()),
-        Asin(sub_expr) => Ok(eval(*sub_expr)?.asin()),
-        Acos(sub_expr) => Ok(eval(*sub_expr)?.acos()),
-        Atan(sub_expr) => Ok(eval(*sub_expr)?.atan()),
-        Arsinh(sub_expr) => Ok(eval(*sub_expr)?.asinh()),
-        Arcosh(sub_expr) => Ok(eval(*sub_expr)?.acosh()),
-        Artanh(sub_expr) => Ok(eval(*sub_expr)?.atanh()),
-        Sqrt(sub_expr) => Ok(eval(*sub_expr)?.sqrt()),
-        Ln(sub_expr) => Ok(eval(*sub_expr)?.ln()),
-        Truncate(sub_expr) => Ok(eval(*sub_expr)?.trunc()),
-        Sign(sub_expr) => Ok(eval(*sub_expr)?.signum()),
-        Exp(sub_expr) => Ok(eval(*sub_expr)?.exp()),
-        Exp2(sub_expr) => Ok(eval(*sub_expr)?.exp2()),
-        Log(expr1, expr2) => Ok(eval(*expr1)?.log(eval(*expr2)?)),
-        Pow2(sub_expr) => {
-            let result = eval(*sub_expr)?;
-            Ok(result * result)
-        }
-        Pow3(sub_expr) => {
-            let result = eval(*sub_expr)?;
-            Ok(result * result * result)
-        }
-        Min(args) => {
-            let mut result = f64::INFINITY;
-            for arg in args {
-                result = eval(arg)?.min(result);
-            }
-            Ok(result)
-        }
-        Max(args) => {
-            let mut result = f64::NEG_INFINITY;
-            for arg in args {
-                result = eval(arg)?.max(result);
-            }
-            Ok(result)
-        }
-        Atan2(expr1, expr2) => Ok(eval(*expr1)?.atan2(eval(*expr2)?)),
+                Value::Complex(root)
+            }
+        })
+        .collect();
+    roots.sort_by(|a, b| match (a, b) {
+        (Value::Number(x), Value::Number(y)) => x.total_cmp(y),
+        (Value::Number(_), _) => Ordering::Less,
+        (_, Value::Number(_)) => Ordering::Greater,
+        (Value::Complex(z1), Value::Complex(z2)) => {
+            z1.re.total_cmp(&z2.re).then(z1.im.total_cmp(&z2.im))
+        }
+        _ => Ordering::Equal,
+    });
+    Value::List(roots)
+}
+
+/// Product of two matrices, a list of numbers being taken as a column vector on the right and
+/// as a row vector on the left, in which case the result is a list as well.
+fn matrix_product(a: &Value, b: &Value) -> Result<Value, EvalError> {
+    match (a, b) {
+        (Value::Matrix(a), Value::Matrix(b)) => Ok(Value::Matrix(a.mul(b)?)),
+        (Value::Matrix(a), vector) => {
+            let product = a.mul(&Matrix::column(vector.as_numbers()?))?;
+            Ok(Value::List(
+                product
+                    .into_elements()
+                    .into_iter()
+                    .map(Value::Number)
+                    .collect(),
+            ))
+        }
+        (vector, b) => {
+            let product = Matrix::row_vector(vector.as_numbers()?).mul(b.as_matrix()?)?;
+            Ok(Value::List(
+                product
+                    .into_elements()
+                    .into_iter()
+                    .map(Value::Number)
+                    .collect(),
+            ))
+        }
+    }
+}
+
+fn not_a_list(value: &Value) -> EvalError {
+    EvalError::TypeMismatch {
+        expected: "a list",
+        got: value.to_string(),
+    }
+}
+
+/// Compute a node from the values of its children.
+fn apply(node: &Node, args: &[Value], context: &mut Context) -> Result<Value, EvalError> {
+    use self::Node::*;
+    // Numbers within the tolerance compare as equal, so that `<` and `==` never both hold.
+    let ordering = |args: &[Value]| -> Result<Option<Ordering>, EvalError> {
+        let (a, b) = (args[0].as_number()?, args[1].as_number()?);
+        if approx_eq(a, b, context.tolerance) {
+            Ok(Some(Ordering::Equal))
+        } else {
+            Ok(a.partial_cmp(&b))
+        }
+    };
+    match node {
+        Boolean(boolean) => Ok(Value::Boolean(*boolean)),
+        Less(..) => Ok(Value::Boolean(ordering(args)? == Some(Ordering::Less))),
+        LessEqual(..) => Ok(Value::Boolean(matches!(
+            ordering(args)?,
+            Some(Ordering::Less | Ordering::Equal)
+        ))),
+        Greater(..) => Ok(Value::Boolean(ordering(args)? == Some(Ordering::Greater))),
+        GreaterEqual(..) => Ok(Value::Boolean(matches!(
+            ordering(args)?,
+            Some(Ordering::Greater | Ordering::Equal)
+        ))),
+        Equal(..) | NotEqual(..) => {
+            let equal = match (&args[0], &args[1]) {
+                (Value::Number(a), Value::Number(b)) => approx_eq(*a, *b, context.tolerance),
+                (Value::Boolean(a), Value::Boolean(b)) => a == b,
+                (a, b) => {
+                    return Err(EvalError::TypeMismatch {
+                        expected: "two numbers or two booleans",
+                        got: format!("{} and {}", a, b),
+                    })
+                }
+            };
+            Ok(Value::Boolean(equal == matches!(node, Equal(..))))
+        }
+        And(..) => Ok(Value::Boolean(
+            args[0].as_boolean()? && args[1].as_boolean()?,
+        )),
+        Or(..) => Ok(Value::Boolean(
+            args[0].as_boolean()? || args[1].as_boolean()?,
+        )),
+        Not(_) => Ok(Value::Boolean(!args[0].as_boolean()?)),
+        Derivative(variable, _, function) => {
+            let x = args[0].as_number()?;
+            let estimate = outermost_estimate(context, |context| {
+                numeric::derivative(&mut |x| eval_at(function, variable, x, context), x)
+            })?;
+            Ok(Value::Number(estimate))
+        }
+        Limit(variable, _, function)
+        | LimitLeft(variable, _, function)
+        | LimitRight(variable, _, function) => {
+            let a = args[0].as_number()?;
+            let precision = context.precision;
+            let estimate = outermost_estimate(context, |context| {
+                let mut f = |x| eval_at(function, variable, x, context);
+                limit(node, &mut f, a, precision)
+            })?;
+            Ok(Value::Number(estimate))
+        }
+        Complex(complex) => Ok(Value::Complex(*complex)),
+        List(_) => Ok(Value::from_items(args.to_vec())),
+        Len(_) => match args[0].items() {
+            Some(items) => Ok(Value::Number(items.len() as f64)),
+            None => Err(not_a_list(&args[0])),
+        },
+        Index(..) => {
+            let index = args[1].as_number()?;
+            let Some(items) = args[0].items() else {
+                return Err(not_a_list(&args[0]));
+            };
+            match items.get(index as usize) {
+                Some(item) if index.fract() == 0.0 && index >= 0.0 => Ok(item.clone()),
+                _ => Err(EvalError::IndexOutOfBounds {
+                    index,
+                    length: items.len(),
+                }),
+            }
+        }
+        Multiply(..)
+            if matches!(
+                args,
+                [Value::Matrix(_), Value::Matrix(_) | Value::List(_)]
+                    | [Value::List(_), Value::Matrix(_)]
+            ) =>
+        {
+            matrix_product(&args[0], &args[1])
+        }
+        LeftDivide(..) => {
+            let a = args[0].as_matrix()?;
+            match &args[1] {
+                Value::Matrix(b) => Ok(Value::Matrix(a.solve(b)?)),
+                vector => {
+                    let x = a.solve(&Matrix::column(vector.as_numbers()?))?;
+                    Ok(Value::List(
+                        x.into_elements().into_iter().map(Value::Number).collect(),
+                    ))
+                }
+            }
+        }
+        Transpose(_) => Ok(Value::Matrix(args[0].as_matrix()?.transpose())),
+        Determinant(_) => Ok(Value::Number(args[0].as_matrix()?.determinant()?)),
+        Inverse(_) => Ok(Value::Matrix(args[0].as_matrix()?.inverse()?)),
+        Trace(_) => Ok(Value::Number(args[0].as_matrix()?.trace()?)),
+        Rank(_) => Ok(Value::Number(
+            args[0].as_matrix()?.rank(context.tolerance) as f64
+        )),
+        Identity(_) => {
+            let size = args[0].as_number()?;
+            if size.fract() != 0.0 || size < 1.0 {
+                return Err(EvalError::Domain {
+                    function: "identity",
+                    arguments: vec![size],
+                });
+            }
+            if size > matrix::MAX_DIMENSION as f64 {
+                return Err(EvalError::TooLarge {
+                    what: "size of a matrix",
+                    limit: matrix::MAX_DIMENSION,
+                });
+            }
+            Ok(Value::Matrix(Matrix::identity(size as usize)))
+        }
+        Range(..) => {
+            let (from, to) = (args[0].as_number()?, args[1].as_number()?);
+            let length = ((to - from).floor() + 1.0).max(0.0);
+            if length > MAX_RANGE_LENGTH as f64 {
+                return Err(EvalError::TooLarge {
+                    what: "length of a range",
+                    limit: MAX_RANGE_LENGTH as usize,
+                });
+            }
+            Ok(Value::List(
+                (0..(length as u64))
+                    .map(|index| Value::Number(from + index as f64))
+                    .collect(),
+            ))
+        }
+        Roots(variable, polynomial) => {
+            let coefficients = polynomial::coefficients(polynomial, variable, &mut |node| {
+                eval_node(node, context)?.as_number()
+            })?;
+            Ok(root_values(polynomial::roots(&coefficients)?))
+        }
+        IsPrime(_) => {
+            let is_prime = |value: &Value| -> Result<Value, EvalError> {
+                Ok(Value::Boolean(number_theory::is_prime(value.as_number()?)?))
+            };
+            match args[0].items() {
+                Some(items) => Ok(Value::from_items(
+                    items.iter().map(is_prime).collect::<Result<_, _>>()?,
+                )),
+                None => is_prime(&args[0]),
+            }
+        }
+        Financial(finance::Financial::Amortize, _) => {
+            let args = args
+                .iter()
+                .map(Value::as_number)
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(Value::Matrix(finance::amortize(args[0], args[1], args[2])?))
+        }
+        Choice(_) => match args[0].items() {
+            Some(items) if !items.is_empty() => {
+                let index = context.rng().gen_range(0..items.len());
+                Ok(items[index].clone())
+            }
+            Some(_) => Err(EvalError::TypeMismatch {
+                expected: "a non empty list",
+                got: args[0].to_string(),
+            }),
+            None => Err(not_a_list(&args[0])),
+        },
+        Factor(_) => Ok(Value::List(
+            number_theory::factor(args[0].as_number()?)?
+                .into_iter()
+                .map(Value::Number)
+                .collect(),
+        )),
+        Eigenvalues(_) => Ok(root_values(args[0].as_matrix()?.eigenvalues()?)),
+        Error => Err(EvalError::SyntaxError),
+        Variable(name) => match context.variable(name) {
+            Some(value) => Ok(value.clone()),
+            None => match constants::find(name) {
+                Some(constant) => Ok(Value::Number(constant.value)),
+                None => Err(EvalError::undefined_variable(name)),
+            },
+        },
+        Sum(variable, _, _, body) | Product(variable, _, _, body) => {
+            let (from, to) = (args[0].as_number()?, args[1].as_number()?);
+            let terms = (to - from).floor() + 1.0;
+            if terms > MAX_SERIES_TERMS as f64 {
+                return Err(EvalError::TooLarge {
+                    what: "number of terms",
+                    limit: MAX_SERIES_TERMS as usize,
+                });
+            }
+            let mut result = if matches!(node, Sum(..)) { 0.0 } else { 1.0 };
+            for index in 0..(terms as u64) {
+                context.bind(variable, Value::Number(from + index as f64));
+                let term = eval_node(body, context);
+                context.unbind();
+                match node {
+                    Sum(..) => result += term?.as_number()?,
+                    _ => result *= term?.as_number()?,
+                }
+            }
+            Ok(Value::Number(result))
+        }
+        Integrate(variable, _, _, integrand) => {
+            let (from, to) = (args[0].as_number()?, args[1].as_number()?);
+            let precision = context.precision;
+            let integral = outermost_estimate(context, |context| {
+                let mut f = |x| eval_at(integrand, variable, x, context);
+                numeric::integrate(&mut f, from, to, precision)
+            })?;
+            Ok(Value::Number(integral))
+        }
+        Root(variable, _, _, function) => {
+            let (a, b) = (args[0].as_number()?, args[1].as_number()?);
+            let precision = context.precision;
+            let mut f = |x| eval_at(function, variable, x, context);
+            Ok(Value::Number(numeric::brent(&mut f, a, b, precision)?))
+        }
+        Solve(variable, _, function) => {
+            let guess = args[0].as_number()?;
+            let precision = context.precision;
+            if let Some(derivative) = derivative(function, variable) {
+                let root = numeric::newton(
+                    &mut |x| {
+                        let y = eval_at(function, variable, x, context)?;
+                        Ok((y, eval_at(&derivative, variable, x, context)?))
+                    },
+                    guess,
+                    precision,
+                );
+                if let Some(root) = root? {
+                    return Ok(Value::Number(root));
+                }
+            }
+            let mut f = |x| eval_at(function, variable, x, context);
+            let (a, b) = numeric::bracket(&mut f, guess)?;
+            Ok(Value::Number(numeric::brent(&mut f, a, b, precision)?))
+        }
+        Min(_)
+        | Max(_)
+        | Total(_)
+        | Mean(_)
+        | Median(_)
+        | Mode(_)
+        | Variance(_)
+        | PopulationVariance(_)
+        | StandardDeviation(_)
+        | PopulationStandardDeviation(_)
+        | Quantile(_)
+        | GeometricMean(_)
+        | HarmonicMean(_)
+        | Skewness(_)
+        | Kurtosis(_)
+        | Gcd(_)
+        | Lcm(_) => {
+            let args = flatten(args)
+                .iter()
+                .map(Value::as_number)
+                .collect::<Result<Vec<_>, _>>()?;
+            apply_strict(node, &args, context).map(Value::Number)
+        }
+        Financial(function, _) if function.cash_flows().is_some() => {
+            let start = function.cash_flows().unwrap_or(0).min(args.len());
+            let (fixed, cash_flows) = args.split_at(start);
+            let args = fixed
+                .iter()
+                .cloned()
+                .chain(flatten(cash_flows))
+                .map(|value| value.as_number())
+                .collect::<Result<Vec<_>, _>>()?;
+            apply_strict(node, &args, context).map(Value::Number)
+        }
+        _ => broadcast(node, args, context),
+    }
+}
+
+/// The elements of `args`, those of nested lists included.
+fn flatten(args: &[Value]) -> Vec<Value> {
+    let mut items = Vec::new();
+    let mut pending: Vec<&Value> = args.iter().rev().collect();
+    while let Some(value) = pending.pop() {
+        match value {
+            Value::List(values) => pending.extend(values.iter().rev()),
+            Value::Matrix(matrix) => {
+                items.extend(matrix.elements().iter().map(|x| Value::Number(*x)))
+            }
+            value => items.push(value.clone()),
+        }
+    }
+    items
+}
+
+/// Compute a node element-wise when some of its arguments are lists, which must all have the
+/// same length, the other arguments being repeated for each element. Matrices are gone through
+/// row by row.
+fn broadcast(node: &Node, args: &[Value], context: &mut Context) -> Result<Value, EvalError> {
+    let items = args.iter().map(Value::items).collect::<Vec<_>>();
+    let mut lengths = items.iter().flatten().map(Vec::len);
+    let Some(length) = lengths.next() else {
+        let args = args
+            .iter()
+            .map(Value::as_number)
+            .collect::<Result<Vec<_>, _>>()?;
+        return apply_strict(node, &args, context).map(Value::Number);
+    };
+    if let Some(other) = lengths.find(|other| *other != length) {
+        return Err(EvalError::DimensionMismatch(format!(
+            "cannot combine lists of length {} and {}",
+            length, other
+        )));
+    }
+    (0..length)
+        .map(|index| {
+            let element_args = args
+                .iter()
+                .zip(&items)
+                .map(|(arg, items)| match items {
+                    Some(items) => items[index].clone(),
+                    None => arg.clone(),
+                })
+                .collect::<Vec<_>>();
+            broadcast(node, &element_args, context)
+        })
+        .collect::<Result<Vec<_>, _>>()
+        .map(Value::from_items)
+}
+
+/// Compute a node whose children are all numbers, checking the result under the strict policy.
+fn apply_strict(node: &Node, args: &[f64], context: &mut Context) -> Result<f64, EvalError> {
+    let result = apply_numeric(node, args, context)?;
+    if context.float_policy == FloatPolicy::Strict {
+        check_strict(node, args, result)?;
+    }
+    Ok(result)
+}
+
+/// Whether `args` are at a pole of the function of `node`, where it's infinite.
+fn at_pole(node: &Node, args: &[f64]) -> bool {
+    use self::Node::*;
+    let non_positive_integer = |x: f64| x.fract() == 0.0 && x <= 0.0;
+    match node {
+        Ln(_) => args[0] == 0.0,
+        Log(..) => args[0] == 0.0 || args[1] == 1.0,
+        Factorial(_) => args[0].fract() == 0.0 && args[0] < 0.0,
+        Gamma(_) | LnGamma(_) | Digamma(_) => non_positive_integer(args[0]),
+        Beta(..) => args.iter().any(|x| non_positive_integer(*x)),
+        Zeta(_) => args[0] == 1.0,
+        Artanh(_) | ErfInv(_) => args[0].abs() == 1.0,
+        BesselY(..) | BesselK(..) => args[1] == 0.0,
+        _ => false,
+    }
+}
+
+/// Reject what the strict policy forbids: a division by zero, a `NaN` out of numbers, or an
+/// infinity out of finite numbers. An infinity is taken as a pole when the function has one
+/// there, as for `ln(0)`, `zeta(1)` or `digamma(-2)`, and as an overflow otherwise.
+fn check_strict(node: &Node, args: &[f64], result: f64) -> Result<(), EvalError> {
+    use self::Node::*;
+    if args.is_empty() {
+        // Literals such as `inf` and `nan` are what the user asked for.
+        return Ok(());
+    }
+    let function = node.name();
+    let arguments = args.to_vec();
+    let divides_by_zero = match node {
+        Divide(..) | Modulo(..) | IntegerDivide(..) => args[1] == 0.0,
+        Caret(..) | Pow(..) => args[0] == 0.0 && args[1] < 0.0,
+        _ => false,
+    };
+    if divides_by_zero {
+        return Err(EvalError::DivisionByZero {
+            function,
+            arguments,
+        });
+    }
+    if result.is_nan() && !args.iter().any(|x| x.is_nan()) {
+        return Err(EvalError::Domain {
+            function,
+            arguments,
+        });
+    }
+    if result.is_infinite() && args.iter().all(|x| x.is_finite()) {
+        return Err(if at_pole(node, args) {
+            EvalError::Domain {
+                function,
+                arguments,
+            }
+        } else {
+            EvalError::Overflow {
+                function,
+                arguments,
+            }
+        });
+    }
+    Ok(())
+}
+
+/// Compute a node whose children are all numbers.
+fn apply_numeric(node: &Node, args: &[f64], context: &mut Context) -> Result<f64, EvalError> {
+    use self::Node::*;
+    match node {
+        // Integer literals out of the programmer mode's range wrap or overflow like results do,
+        // the operators reject the other ones.
+        Number(i) => match context.integer_mode {
+            Some(mode) if i.fract() == 0.0 => Ok(mode.integer_from(*i)? as f64),
+            _ => Ok(*i),
+        },
+        Add(..) => arithmetic_op(context, args[0], args[1], IntegerMode::add, |a, b| a + b),
+        Subtract(..) => arithmetic_op(context, args[0], args[1], IntegerMode::sub, |a, b| a - b),
+        Multiply(..) => arithmetic_op(context, args[0], args[1], IntegerMode::mul, |a, b| a * b),
+        Divide(..) => arithmetic_op(context, args[0], args[1], IntegerMode::div, |a, b| a / b),
+        Modulo(..) => arithmetic_op(context, args[0], args[1], IntegerMode::rem, |a, b| a % b),
+        IntegerDivide(..) => {
+            arithmetic_op(context, args[0], args[1], IntegerMode::floor_div, |a, b| {
+                (a / b).floor()
+            })
+        }
+        BitAnd(..) => integer_op(context, args[0], args[1], IntegerMode::and),
+        BitOr(..) => integer_op(context, args[0], args[1], IntegerMode::or),
+        BitXor(..) => integer_op(context, args[0], args[1], IntegerMode::xor),
+        ShiftLeft(..) => integer_op(context, args[0], args[1], IntegerMode::shl),
+        ShiftRight(..) => integer_op(context, args[0], args[1], IntegerMode::shr),
+        BitNot(_) => integer_op(context, args[0], 0.0, |mode, a, _| mode.not(a)),
+        Negative(_) => match context.integer_mode {
+            Some(mode) => Ok(mode.neg(mode.integer_from(args[0])?)? as f64),
+            None => Ok(-args[0]),
+        },
+        Caret(..) | Pow(..) => Ok(args[0].powf(args[1])),
+        Factorial(_) => Ok(factorial(args[0])),
+        Abs(_) => Ok(args[0].abs()),
+        Floor(_) => Ok(args[0].floor()),
+        Ceil(_) => Ok(args[0].ceil()),
+        Round(_) => Ok(args[0].round()),
+        Sin(_) => Ok(args[0].sin()),
+        Cos(_) => Ok(args[0].cos()),
+        Tan(_) => Ok(args[0].tan()),
+        Sinh(_) => Ok(args[0].sinh()),
+        Cosh(_) => Ok(args[0].cosh()),
+        Tanh(_) => Ok(args[0].tanh()),
+        Asin(_) => Ok(args[0].asin()),
+        Acos(_) => Ok(args[0].acos()),
+        Atan(_) => Ok(args[0].atan()),
+        Arsinh(_) => Ok(args[0].asinh()),
+        Arcosh(_) => Ok(args[0].acosh()),
+        Artanh(_) => Ok(args[0].atanh()),
+        Sqrt(_) => Ok(args[0].sqrt()),
+        Ln(_) => Ok(args[0].ln()),
+        Truncate(_) => Ok(args[0].trunc()),
+        Sign(_) => Ok(args[0].signum()),
+        Exp(_) => Ok(args[0].exp()),
+        Exp2(_) => Ok(args[0].exp2()),
+        Log(..) => Ok(args[0].log(args[1])),
+        Pow2(_) => Ok(args[0] * args[0]),
+        Pow3(_) => Ok(args[0] * args[0] * args[0]),
+        Min(_) => Ok(args
+            .iter()
+            .fold(f64::INFINITY, |result, arg| arg.min(result))),
+        Max(_) => Ok(args
+            .iter()
+            .fold(f64::NEG_INFINITY, |result, arg| arg.max(result))),
+        Atan2(..) => Ok(args[0].atan2(args[1])),
+        Gamma(_) => Ok(special::gamma(args[0])),
+        LnGamma(_) => Ok(special::ln_gamma(args[0])),
+        Digamma(_) => Ok(digamma(args[0])),
+        Beta(..) => Ok(special::beta(args[0], args[1])),
+        Erf(_) => Ok(erf(args[0])),
+        Erfc(_) => Ok(erfc(args[0])),
+        ErfInv(_) if args[0].abs() > 1.0 => Ok(f64::NAN),
+        ErfInv(_) => Ok(erf_inv(args[0])),
+        BesselJ(..) => special::bessel_j(args[0], args[1]),
+        BesselY(..) => special::bessel_y(args[0], args[1]),
+        BesselI(..) => special::bessel_i(args[0], args[1]),
+        BesselK(..) => special::bessel_k(args[0], args[1]),
+        Zeta(_) => Ok(special::zeta(args[0])),
+        LambertW(_) => Ok(special::lambert_w(args[0])),
+        GammaInc(..) => Ok(special::gamma_inc(args[0], args[1])),
+        GammaIncC(..) => Ok(special::gamma_inc_complement(args[0], args[1])),
+        BetaInc(..) => Ok(special::beta_inc(args[0], args[1], args[2])),
+        Binomial(..) => number_theory::binomial(args[0], args[1]),
+        Permutations(..) => number_theory::permutations(args[0], args[1]),
+        Gcd(_) => number_theory::gcd(args),
+        Lcm(_) => number_theory::lcm(args),
+        NextPrime(_) => number_theory::next_prime(args[0]),
+        Totient(_) => number_theory::totient(args[0]),
+        ModPow(..) => number_theory::mod_pow(args[0], args[1], args[2]),
+        ModInv(..) => number_theory::mod_inv(args[0], args[1]),
+        Fibonacci(_) => number_theory::fibonacci(args[0]),
+        DoubleFactorial(_) => number_theory::double_factorial(args[0]),
+        Subfactorial(_) => number_theory::subfactorial(args[0]),
+        Rand => Ok(context.rng().gen()),
+        RandInt(..) => random_integer(args[0], args[1], context.rng()),
+        Sample(distribution, _) => distribution::sample(*distribution, args, context.rng()),
+        Financial(function, _) => finance::evaluate(*function, args, context.precision),
+        Total(_) => Ok(args.iter().sum()),
+        Mean(_) => statistics::mean(args),
+        Median(_) => statistics::median(args),
+        Mode(_) => statistics::mode(args),
+        Variance(_) => statistics::variance(args, "var"),
+        PopulationVariance(_) => statistics::population_variance(args, "varp"),
+        StandardDeviation(_) => statistics::variance(args, "stdev").map(f64::sqrt),
+        PopulationStandardDeviation(_) => {
+            statistics::population_variance(args, "stdevp").map(f64::sqrt)
+        }
+        Quantile(_) => match args.split_last() {
+            Some((p, data)) => statistics::quantile(data, *p),
+            None => Err(EvalError::ArityMismatch {
+                function: "quantile",
+                expected: "at least 2".to_string(),
+                got: 0,
+            }),
+        },
+        GeometricMean(_) => statistics::geometric_mean(args),
+        HarmonicMean(_) => statistics::harmonic_mean(args),
+        Skewness(_) => statistics::skewness(args),
+        Kurtosis(_) => statistics::kurtosis(args),
+        Distribution(distribution, function, _) => {
+            distribution::evaluate(*distribution, *function, args, context.precision)
+        }
+        _ => Err(EvalError::TypeMismatch {
+            expected: "a number",
+            got: format!("{:?}", node),
+        }),
     }
 }
 
@@ -167,6 +1516,824 @@ mod tests {
         assert_eq!(value, 72.0);
     }
     #[test]
+    fn test_power_associativity() {
+        use crate::calcparse::parser::Parser;
+
+        let eval_str = |expr| eval(Parser::new(expr, None).unwrap().parse().unwrap()).unwrap();
+        assert_eq!(eval_str("2^3^2"), 512.0);
+        assert_eq!(eval_str("(2^3)^2"), 64.0);
+        assert_eq!(eval_str("-2^2"), -4.0);
+        assert_eq!(eval_str("(-2)^2"), 4.0);
+        assert_eq!(eval_str("2^-2"), 0.25);
+        assert_eq!(eval_str("-3²"), -9.0);
+    }
+    #[test]
+    fn test_scientific_notation() {
+        use crate::calcparse::parser::Parser;
+
+        let ast = Parser::new("1.5e-3*2*e", None).unwrap().parse().unwrap();
+        let value = eval(ast).unwrap();
+        assert_eq!(value, 1.5e-3 * 2.0 * std::f64::consts::E);
+    }
+    #[test]
+    fn test_bitwise() {
+        use crate::calcparse::parser::Parser;
+
+        let ast = Parser::new("|-3|+(12&10|1)xor~0<<2", None)
+            .unwrap()
+            .parse()
+            .unwrap();
+        let value = eval(ast).unwrap();
+        assert_eq!(value, ((3 + (12 & 10 | 1)) ^ (!0 << 2)) as f64);
+    }
+    #[test]
+    fn test_programmer_mode() {
+        use crate::calcparse::parser::Parser;
+        use crate::calcparse::programmer::Overflow;
+
+        let mut wrapping = Context::default();
+        wrapping.integer_mode = IntegerMode::from_width("u8", Overflow::Wrapping);
+        let mut checked = Context::default();
+        checked.integer_mode = IntegerMode::from_width("u8", Overflow::Checked);
+        let ast = Parser::new("200+100", None).unwrap().parse().unwrap();
+        assert_eq!(eval_with(ast.clone(), &mut wrapping).unwrap(), 44.0);
+        assert!(eval_with(ast, &mut checked).is_err());
+        let ast = Parser::new("-7//2+7/2", None).unwrap().parse().unwrap();
+        assert_eq!(eval(ast.clone()).unwrap(), -0.5);
+        assert_eq!(eval_with(ast, &mut wrapping).unwrap(), 127.0);
+        let ast = Parser::new("300", None).unwrap().parse().unwrap();
+        assert_eq!(eval_with(ast.clone(), &mut wrapping).unwrap(), 44.0);
+        assert!(matches!(
+            eval_with(ast, &mut checked),
+            Err(EvalError::IntegerOverflow(_))
+        ));
+        let ast = Parser::new("255 + 0", None).unwrap().parse().unwrap();
+        assert_eq!(eval_with(ast, &mut checked).unwrap(), 255.0);
+    }
+    #[test]
+    fn test_long_formula() {
+        use crate::calcparse::parser::Parser;
+
+        let expr = format!("0{}", "+1-2*3".repeat(100_000));
+        let ast = Parser::new(&expr, None).unwrap().parse().unwrap();
+        let value = eval(ast).unwrap();
+        assert_eq!(value, -500_000.0);
+    }
+    #[test]
+    fn test_comparisons() {
+        use crate::calcparse::parser::Parser;
+
+        let ast = Parser::new("0.1+0.2==0.3 and not 2<1", None)
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert_eq!(eval(ast).unwrap(), true);
+        let ast = Parser::new("abs(@-3.14)<0.01", Some(Value::Number(3.145)))
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert_eq!(eval(ast).unwrap(), true);
+        let ast = Parser::new("1<2<3", None).unwrap().parse().unwrap();
+        assert!(eval(ast).is_err());
+    }
+    #[test]
+    fn test_short_circuit() {
+        use crate::calcparse::parser::Parser;
+
+        let ast = Parser::new("(false)and(zzz>1)", None)
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert_eq!(eval(ast).unwrap(), false);
+        let ast = Parser::new("(true)or(zzz>1)", None)
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert_eq!(eval(ast).unwrap(), true);
+        let ast = Parser::new("(true)and(zzz>1)", None)
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert!(eval(ast).is_err());
+        let ast = Parser::new("(false)or(1)", None).unwrap().parse().unwrap();
+        assert!(eval(ast).is_err());
+    }
+    #[test]
+    fn test_equality_tolerance() {
+        use crate::calcparse::parser::Parser;
+
+        let mut exact = Context::default();
+        exact.tolerance = 0.0;
+        let ast = Parser::new("0.1+0.2==0.3", None).unwrap().parse().unwrap();
+        assert_eq!(eval_with(ast, &mut exact).unwrap(), false);
+        let ast = Parser::new("0.1+0.2<=0.3", None).unwrap().parse().unwrap();
+        assert_eq!(eval(ast).unwrap(), true);
+    }
+    #[test]
+    fn test_conditionals() {
+        use crate::calcparse::parser::Parser;
+
+        let ast = Parser::new("if(2>1,10,20)+(1>2?1:2>1?2:3)", None)
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert_eq!(eval(ast).unwrap(), 12.0);
+        // The untaken branch would be a type error if it was evaluated.
+        let ast = Parser::new("if(true,1,1+true)", None)
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert_eq!(eval(ast).unwrap(), 1.0);
+    }
+    #[test]
+    fn test_piecewise() {
+        use crate::calcparse::parser::Parser;
+
+        let tax = "piecewise(@<=10000,0,@<=40000,(@-10000)*0.2,6000+(@-40000)*0.4)";
+        let tax_for = |income: f64| {
+            let ast = Parser::new(tax, Some(Value::Number(income)))
+                .unwrap()
+                .parse()
+                .unwrap();
+            eval(ast).unwrap()
+        };
+        assert_eq!(tax_for(5000.0), 0.0);
+        assert_eq!(tax_for(20000.0), 2000.0);
+        assert_eq!(tax_for(50000.0), 10000.0);
+        assert!(Parser::new("piecewise(true,1)", None)
+            .unwrap()
+            .parse()
+            .is_err());
+    }
+    #[test]
+    fn test_sum_and_product() {
+        use crate::calcparse::parser::Parser;
+
+        let ast = Parser::new("sum(k, 1, 100, k^2)", None)
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert_eq!(eval(ast).unwrap(), 338350.0);
+        let ast = Parser::new("prod(k, 1, 5, k) + sum(i, 1, 3, sum(j, 1, i, i j))", None)
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert_eq!(eval(ast).unwrap(), 120.0 + 1.0 + 6.0 + 18.0);
+        let ast = Parser::new("sum(k, 1, 0, k) + prod(k, 1, 0, k)", None)
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert_eq!(eval(ast).unwrap(), 1.0);
+    }
+    #[test]
+    fn test_integrate() {
+        use crate::calcparse::parser::Parser;
+
+        let mut context = Context::default();
+        let ast = Parser::new("integrate(x^2, x, 0, 3)", None)
+            .unwrap()
+            .parse()
+            .unwrap();
+        let value = eval_with(ast, &mut context).unwrap().as_number().unwrap();
+        assert!((value - 9.0).abs() < 1e-12);
+        assert!(context.estimated_error.unwrap() < 1e-9);
+        let ast = Parser::new("integrate(exp(-(x^2)), x, -1/0, 1/0)^2", None)
+            .unwrap()
+            .parse()
+            .unwrap();
+        let value = eval(ast).unwrap().as_number().unwrap();
+        assert!((value - std::f64::consts::PI).abs() < 1e-9);
+        let ast = Parser::new("integrate(integrate(x y, y, 0, x), x, 0, 1)", None)
+            .unwrap()
+            .parse()
+            .unwrap();
+        let value = eval(ast).unwrap().as_number().unwrap();
+        assert!((value - 0.125).abs() < 1e-12);
+        // Only the error of the outer integral is reported, the inner ones would add up over
+        // every sample.
+        let ast = Parser::new("integrate(integrate(sin(x y), y, 0, x), x, 0, 3)", None)
+            .unwrap()
+            .parse()
+            .unwrap();
+        eval_with(ast, &mut context).unwrap();
+        assert!(context.estimated_error.unwrap() < 1e-10);
+    }
+    #[test]
+    fn test_solve() {
+        use crate::calcparse::parser::Parser;
+
+        let ast = Parser::new("solve(x^2 = 2, x, 1)", None)
+            .unwrap()
+            .parse()
+            .unwrap();
+        let value = eval(ast).unwrap().as_number().unwrap();
+        assert!((value - 2f64.sqrt()).abs() < 1e-12);
+        // No derivative is known for floor, the root is bracketed instead.
+        let ast = Parser::new("solve(floor(x) + x = 8.5, x, 0)", None)
+            .unwrap()
+            .parse()
+            .unwrap();
+        let value = eval(ast).unwrap().as_number().unwrap();
+        assert!((value - 4.5).abs() < 1e-9);
+        let ast = Parser::new("root(cos(x) - x, x, 0, 1)", None)
+            .unwrap()
+            .parse()
+            .unwrap();
+        let value = eval(ast).unwrap().as_number().unwrap();
+        assert!((value - 0.739_085_133_215_160_6).abs() < 1e-9);
+        let ast = Parser::new("root(x^2 + 1, x, -1, 1)", None)
+            .unwrap()
+            .parse()
+            .unwrap();
+        let error = eval(ast).unwrap_err().to_string();
+        assert!(error.starts_with("No sign change in bracket"));
+    }
+    #[test]
+    fn test_roots() {
+        use crate::calcparse::parser::Parser;
+
+        let ast = Parser::new("roots(x^3 - 6x^2 + 11x - 6)", None)
+            .unwrap()
+            .parse()
+            .unwrap();
+        let roots = match eval(ast).unwrap() {
+            Value::List(roots) => roots,
+            value => panic!("Expected a list, got {}", value),
+        };
+        assert_eq!(roots.len(), 3);
+        for (root, expected) in roots.iter().zip([1.0, 2.0, 3.0]) {
+            assert!((root.as_number().unwrap() - expected).abs() < 1e-12);
+        }
+        let ast = Parser::new("roots(t² + 2t + 5, t)", None)
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert_eq!(eval(ast).unwrap().to_string(), "[-1.0 - 2.0i, -1.0 + 2.0i]");
+        assert!(Parser::new("roots(x y)", None).unwrap().parse().is_err());
+        let ast = Parser::new("roots(sin(x))", None).unwrap().parse().unwrap();
+        assert!(eval(ast).is_err());
+    }
+    #[test]
+    fn test_derivative_and_limit() {
+        use crate::calcparse::parser::Parser;
+
+        let eval_str = |expr| {
+            let ast = Parser::new(expr, None).unwrap().parse().unwrap();
+            eval(ast).map(|value| value.as_number().unwrap())
+        };
+        assert!((eval_str("deriv(x^3, x, 2)").unwrap() - 12.0).abs() < 1e-10);
+        // Gamma's derivative at 1 is minus the Euler–Mascheroni constant.
+        let value = eval_str("deriv(x!, x, 0)").unwrap();
+        assert!((value + 0.577_215_664_901_532_9).abs() < 1e-9);
+        assert!((eval_str("limit(sin(x)/x, x, 0)").unwrap() - 1.0).abs() < 1e-10);
+        assert_eq!(eval_str("limit_right(1/x, x, 0)").unwrap(), f64::INFINITY);
+        assert_eq!(
+            eval_str("limit_left(1/x, x, 0)").unwrap(),
+            f64::NEG_INFINITY
+        );
+        assert!(eval_str("limit(abs(x)/x, x, 0)").is_err());
+        assert!((eval_str("limit(x sin(1/x), x, 1/0)").unwrap() - 1.0).abs() < 1e-9);
+    }
+    #[test]
+    fn test_lists() {
+        use crate::calcparse::parser::Parser;
+
+        let eval_str = |expr| {
+            let ast = Parser::new(expr, None).unwrap().parse().unwrap();
+            eval(ast).map(|value| value.to_string())
+        };
+        assert_eq!(eval_str("[1, 2, 3] * 2 + 1").unwrap(), "[3.0, 5.0, 7.0]");
+        assert_eq!(eval_str("[1, 4] + [2, 3]").unwrap(), "[3.0, 7.0]");
+        assert_eq!(eval_str("sqrt([4, 9, [16]])").unwrap(), "[2.0, 3.0, [4.0]]");
+        assert_eq!(eval_str("[10, 20, 30][1] + len(1..10)").unwrap(), "30.0");
+        assert_eq!(eval_str("2..2+2").unwrap(), "[2.0, 3.0, 4.0]");
+        assert_eq!(eval_str("max(1..4, [7], 2)").unwrap(), "7.0");
+        assert_eq!(eval_str("len([]) + len(2..1)").unwrap(), "0.0");
+        assert!(eval_str("[1, 2] + [1, 2, 3]").is_err());
+        assert!(eval_str("[1, 2][2]").is_err());
+        assert!(eval_str("[1, 2][0.5]").is_err());
+        assert!(eval_str("len(3)").is_err());
+        assert!(eval_str("1..1e9").is_err());
+    }
+    #[test]
+    fn test_index_bounds() {
+        use crate::calcparse::parser::Parser;
+
+        let eval_str = |expr| {
+            let ast = Parser::new(expr, None).unwrap().parse().unwrap();
+            eval(ast).map(|value| value.to_string())
+        };
+        // Indices start at 0, the last element being at `len(v) - 1`.
+        assert_eq!(eval_str("[10, 20, 30][0]").unwrap(), "10.0");
+        assert_eq!(
+            eval_str("[10, 20, 30][len([10, 20, 30]) - 1]").unwrap(),
+            "30.0"
+        );
+        assert_eq!(eval_str("(5..7)[2]").unwrap(), "7.0");
+        assert_eq!(eval_str("[[1, 2], [3, 4]][1][0]").unwrap(), "3.0");
+        assert!(eval_str("[10, 20, 30][3]").is_err());
+        assert!(eval_str("[10, 20, 30][-1]").is_err());
+        assert!(eval_str("[][0]").is_err());
+        assert!(eval_str("(2..1)[0]").is_err());
+    }
+    #[test]
+    fn test_matrices() {
+        use crate::calcparse::parser::Parser;
+
+        let eval_str = |expr| {
+            let ast = Parser::new(expr, None).unwrap().parse().unwrap();
+            eval(ast).map(|value| value.to_string())
+        };
+        assert_eq!(
+            eval_str("[[1, 2], [3, 4]] * [[0, 1], [1, 0]] + 1").unwrap(),
+            "[[3.0, 2.0], [5.0, 4.0]]"
+        );
+        assert_eq!(eval_str("[[1, 2], [3, 4]] * [1, 1]").unwrap(), "[3.0, 7.0]");
+        assert_eq!(eval_str("det([[1, 2], [3, 4]])").unwrap(), "-2.0");
+        assert_eq!(eval_str("transpose([[1, 2, 3]])[2]").unwrap(), "[3.0]");
+        assert_eq!(
+            eval_str("rank([[1, 2], [2, 4]]) + trace(identity(3))").unwrap(),
+            "4.0"
+        );
+        assert_eq!(
+            eval_str("eig(identity(20))").unwrap(),
+            Value::List(vec![Value::Number(1.0); 20]).to_string()
+        );
+        let diagonal: Vec<String> = (0..10)
+            .map(|i| {
+                let row: Vec<String> = (0..10)
+                    .map(|j| if i == j { 10 - i } else { 0 }.to_string())
+                    .collect();
+                format!("[{}]", row.join(", "))
+            })
+            .collect();
+        let expr = format!("eig([{}])", diagonal.join(", "));
+        assert_eq!(eval_str(&expr).unwrap(), eval_str("1..10").unwrap());
+        let ast = Parser::new("eig([[2, 1], [1, 2]])", None)
+            .unwrap()
+            .parse()
+            .unwrap();
+        let eigenvalues = eval(ast).unwrap().as_numbers().unwrap();
+        for (x, expected) in eigenvalues.iter().zip([1.0, 3.0]) {
+            assert!((x - expected).abs() < 1e-12);
+        }
+        let ast = Parser::new(
+            "[[2, 1, -1], [-3, -1, 2], [-2, 1, 2]] \\ [8, -11, -3]",
+            None,
+        )
+        .unwrap()
+        .parse()
+        .unwrap();
+        let x = eval(ast).unwrap().as_numbers().unwrap();
+        for (x, expected) in x.iter().zip([2.0, 3.0, -1.0]) {
+            assert!((x - expected).abs() < 1e-12);
+        }
+        assert!(eval_str("inv([[1, 2], [2, 4]])").is_err());
+        assert!(eval_str("[[1, 2]] * [[1, 2]]").is_err());
+        assert!(eval_str("det([1, 2])").is_err());
+        assert!(eval_str("identity(0.5)").is_err());
+    }
+    #[test]
+    fn test_statistics() {
+        use crate::calcparse::parser::Parser;
+
+        let eval_str = |expr| {
+            let ast = Parser::new(expr, None).unwrap().parse().unwrap();
+            eval(ast).map(|value| value.as_number().unwrap())
+        };
+        assert_eq!(eval_str("mean(1, 2, 3, [4, 5])").unwrap(), 3.0);
+        assert_eq!(eval_str("median(1..10) + mode([1, 2, 2, 3])").unwrap(), 7.5);
+        assert_eq!(eval_str("stdevp(2, 4, 4, 4, 5, 5, 7, 9)").unwrap(), 2.0);
+        assert_eq!(eval_str("sum(1..100) - sum(k, 1, 100, k)").unwrap(), 0.0);
+        assert_eq!(eval_str("sum(1, 2, 3)").unwrap(), 6.0);
+        assert_eq!(eval_str("sum(2, 1, 3, 4)").unwrap(), 10.0);
+        assert_eq!(eval_str("sum([1, 2], 3, 4, 5)").unwrap(), 15.0);
+        assert_eq!(eval_str("quantile(1..9, 0.5)").unwrap(), 5.0);
+        assert!(eval_str("var(1)").is_err());
+        assert!(eval_str("mean([])").is_err());
+        assert!(Parser::new("quantile(1)", None).unwrap().parse().is_err());
+        assert!(Parser::new("mean()", None).unwrap().parse().is_err());
+        assert!(Parser::new("sum()", None).unwrap().parse().is_err());
+    }
+    #[test]
+    fn test_distributions() {
+        use crate::calcparse::parser::Parser;
+
+        let eval_str = |expr| {
+            let ast = Parser::new(expr, None).unwrap().parse().unwrap();
+            eval(ast)
+        };
+        let p = eval_str("2 * (1 - normcdf(1.96, 0, 1))").unwrap();
+        assert!((p.as_number().unwrap() - 0.05).abs() < 1e-4);
+        let x = eval_str("tinv(0.975, 10)").unwrap().as_number().unwrap();
+        assert!((x - 2.228_138_851_986_274).abs() < 1e-9);
+        assert_eq!(eval_str("binompdf(2, 4, 0.5)").unwrap(), 0.375);
+        assert_eq!(
+            eval_str("normcdf([0, 0], 0, 1)").unwrap().to_string(),
+            "[0.5, 0.5]"
+        );
+        assert!(eval_str("normpdf(0, 0, -1)").is_err());
+        assert!(eval_str("poissoncdf(1, -2)").is_err());
+        assert!(Parser::new("normcdf(1)", None).unwrap().parse().is_err());
+    }
+    #[test]
+    fn test_special_functions() {
+        use crate::calcparse::parser::Parser;
+
+        let eval_str = |expr| {
+            let ast = Parser::new(expr, None).unwrap().parse().unwrap();
+            eval(ast).unwrap().as_number().unwrap()
+        };
+        assert!((eval_str("gamma(0.5) ^ 2") - std::f64::consts::PI).abs() < 1e-12);
+        assert!((eval_str("lgamma(171.5)") - 709.143_163_030_928).abs() < 1e-9);
+        assert!((eval_str("erf(1) + erfc(1)") - 1.0).abs() < 1e-15);
+        assert!((eval_str("erfinv(erf(0.3))") - 0.3).abs() < 1e-12);
+        assert!((eval_str("besselj(0, 2.404825557695773)")).abs() < 1e-12);
+        assert!((eval_str("zeta(4)") - std::f64::consts::PI.powi(4) / 90.0).abs() < 1e-14);
+        assert!((eval_str("lambertw(2 * exp(2))") - 2.0).abs() < 1e-14);
+        assert!((eval_str("gammainc(2, 3) + gammaincc(2, 3)") - 1.0).abs() < 1e-14);
+        assert!((eval_str("betainc(1, 1, 0.3)") - 0.3).abs() < 1e-14);
+        assert!(eval_str("erfinv(2)").is_nan());
+        assert!(eval_str("lambertw(-1)").is_nan());
+        assert!(eval_str("gamma(-1)").is_nan());
+        assert_eq!(eval_str("lgamma(-1)"), f64::INFINITY);
+        assert!(eval_str("beta(-1, 2)").is_nan());
+    }
+    #[test]
+    fn test_number_theory() {
+        use crate::calcparse::parser::Parser;
+
+        let eval_str = |expr| {
+            let ast = Parser::new(expr, None).unwrap().parse().unwrap();
+            eval(ast)
+        };
+        assert_eq!(eval_str("nCr(5, 2) + nPr(5, 2)").unwrap(), 30.0);
+        assert_eq!(eval_str("gcd([12, 18], 27)").unwrap(), 3.0);
+        assert_eq!(eval_str("lcm(4, 6, 10)").unwrap(), 60.0);
+        assert_eq!(eval_str("7!!").unwrap(), 105.0);
+        assert_eq!(eval_str("(3!)!").unwrap(), 720.0);
+        assert_eq!(eval_str("2 * 5!! + 1").unwrap(), 31.0);
+        assert_eq!(eval_str("subfactorial(4)").unwrap(), 9.0);
+        assert_eq!(eval_str("fib(20)").unwrap(), 6765.0);
+        assert_eq!(eval_str("totient(10) + nextprime(7)").unwrap(), 15.0);
+        assert_eq!(eval_str("mod_pow(3, 200, 7) + mod_inv(3, 7)").unwrap(), 7.0);
+        assert_eq!(eval_str("isprime(97)").unwrap(), Value::Boolean(true));
+        assert_eq!(
+            eval_str("isprime(1..5)").unwrap().to_string(),
+            "[false, true, true, false, true]"
+        );
+        assert_eq!(
+            eval_str("factor(84)").unwrap().to_string(),
+            "[2.0, 2.0, 3.0, 7.0]"
+        );
+        assert!(eval_str("nCr(4.5, 2)").is_err());
+        assert!(eval_str("fib(-1)").is_err());
+        assert!(eval_str("mod_inv(2, 4)").is_err());
+    }
+    #[test]
+    fn test_random() {
+        use crate::calcparse::parser::Parser;
+
+        let eval_seeded = |expr, seed| {
+            let ast = Parser::new(expr, None).unwrap().parse().unwrap();
+            eval_with(ast, &mut Context::default().with_seed(seed))
+        };
+        let expr = "[rand(), randint(1, 6), randn(0, 1), choice([2, 4, 8]), binomrnd(10, 0.5)]";
+        let first = eval_seeded(expr, 42).unwrap();
+        assert_eq!(first, eval_seeded(expr, 42).unwrap());
+        assert_ne!(first, eval_seeded(expr, 43).unwrap());
+        for seed in 0..100 {
+            let x = eval_seeded("rand()", seed).unwrap().as_number().unwrap();
+            assert!((0.0..1.0).contains(&x));
+            let n = eval_seeded("randint(-2, 2)", seed)
+                .unwrap()
+                .as_number()
+                .unwrap();
+            assert!(n.fract() == 0.0 && (-2.0..=2.0).contains(&n));
+            let x = eval_seeded("choice(1..3)", seed).unwrap();
+            assert!([1.0, 2.0, 3.0].iter().any(|item| x == *item));
+            let x = eval_seeded("exprnd(2)", seed).unwrap().as_number().unwrap();
+            assert!(x >= 0.0);
+        }
+        assert_eq!(eval_seeded("len(normrnd([0, 10, 20], 1))", 0).unwrap(), 3.0);
+        assert!(eval_seeded("randint(3, 1)", 0).is_err());
+        assert!(eval_seeded("randint(1.5, 3)", 0).is_err());
+        assert!(eval_seeded("choice([])", 0).is_err());
+        assert!(eval_seeded("randn(0, -1)", 0).is_err());
+        assert!(Parser::new("rand(1)", None).unwrap().parse().is_err());
+    }
+    #[test]
+    fn test_finance() {
+        use crate::calcparse::parser::Parser;
+
+        let eval_str = |expr| {
+            let ast = Parser::new(expr, None).unwrap().parse().unwrap();
+            eval(ast)
+        };
+        let pmt = eval_str("pmt(0.05 / 12, 360, 200000)").unwrap();
+        assert!((pmt.as_number().unwrap() + 1_073.643_246_024_278).abs() < 1e-9);
+        let irr = eval_str("irr([-100, 55], 60.5)")
+            .unwrap()
+            .as_number()
+            .unwrap();
+        assert!((irr - 0.1).abs() < 1e-9);
+        let npv = eval_str("npv(0.1, [-100, 55, 60.5])")
+            .unwrap()
+            .as_number()
+            .unwrap();
+        assert!(npv.abs() < 1e-12);
+        let fv = eval_str("fv(0.1, 2, -100)").unwrap().as_number().unwrap();
+        assert!((fv - 210.0).abs() < 1e-9);
+        // Functions other than npv and irr apply to each element of a list.
+        let payments = eval_str("pmt([0.01, 0.02], 12, 1000)")
+            .unwrap()
+            .as_numbers()
+            .unwrap();
+        assert_eq!(payments.len(), 2);
+        for (payment, expr) in payments
+            .iter()
+            .zip(["pmt(0.01, 12, 1000)", "pmt(0.02, 12, 1000)"])
+        {
+            assert_eq!(*payment, eval_str(expr).unwrap().as_number().unwrap());
+        }
+        assert!(eval_str("pmt(0.01, [12, 24, 36], [1000, 2000])").is_err());
+        assert!(eval_str("npv([0.1, 0.2], -100, 55)").is_err());
+        let schedule = eval_str("amortize(0.1, 2, 1000)").unwrap();
+        let schedule = schedule.as_matrix().unwrap();
+        assert_eq!(schedule.rows(), 2);
+        let payment = eval_str("pmt(0.1, 2, 1000)").unwrap();
+        assert_eq!(schedule.row(0)[1], payment.as_number().unwrap());
+        assert!(eval_str("npv(0.1, [])").is_err());
+        assert!(Parser::new("pmt(0.1, 2)", None).unwrap().parse().is_err());
+        assert!(Parser::new("amortize(0.1, 2, 1000, 0)", None)
+            .unwrap()
+            .parse()
+            .is_err());
+    }
+    #[test]
+    fn test_constants() {
+        use crate::calcparse::parser::Parser;
+
+        let eval_str = |expr| {
+            let ast = Parser::new(expr, None).unwrap().parse().unwrap();
+            eval(ast).unwrap()
+        };
+        assert_eq!(eval_str("c"), 299_792_458.0);
+        assert_eq!(eval_str("tau / 2"), std::f64::consts::PI);
+        assert_eq!(eval_str("2pi"), std::f64::consts::TAU);
+        assert_eq!(eval_str("π"), std::f64::consts::PI);
+        assert_eq!(eval_str("e"), std::f64::consts::E);
+        assert_eq!(eval_str("k_B * N_A"), 1.380_649e-23 * 6.022_140_76e23);
+        assert_eq!(
+            eval_str("1 / (e0 * mu0)"),
+            1.0 / (8.854_187_812_8e-12 * 1.256_637_062_12e-6)
+        );
+        // Bound variables shadow constants
+        assert_eq!(eval_str("sum(e, 1, 3, e)"), 6.0);
+        assert_eq!(eval_str("sum(pi, 1, 3, pi)"), 6.0);
+        assert_eq!(eval_str("sum(c, 1, 3, c)"), 6.0);
+        assert_eq!(eval_str("roots(x^2 - phi - 1)"), eval_str("[-phi, phi]"));
+        assert_eq!(eval_str("roots(c^2 - 4)"), eval_str("[-2, 2]"));
+        assert_eq!(eval_str("roots(e^2 - 4)"), eval_str("[-2, 2]"));
+        let mut context = Context::default();
+        context.bind("pi", Value::Number(3.0));
+        let ast = Parser::new("pi + e", None).unwrap().parse().unwrap();
+        assert_eq!(
+            eval_with(ast, &mut context).unwrap(),
+            Value::Number(3.0 + std::f64::consts::E)
+        );
+        // So do the variables of the user
+        context.bind("c", Value::Number(2.0));
+        let ast = Parser::new("c * h", None).unwrap().parse().unwrap();
+        assert_eq!(
+            eval_with(ast, &mut context).unwrap(),
+            Value::Number(2.0 * 6.626_070_15e-34)
+        );
+    }
+    #[test]
+    fn test_float_policy() {
+        use crate::calcparse::parser::Parser;
+
+        let eval_str = |expr, float_policy| {
+            let ast = Parser::new(expr, None).unwrap().parse().unwrap();
+            let mut context = Context::default();
+            context.float_policy = float_policy;
+            eval_with(ast, &mut context).map(|value| value.as_number().unwrap())
+        };
+        let strict_error = |expr| eval_str(expr, FloatPolicy::Strict).unwrap_err();
+        assert_eq!(eval_str("1/0", FloatPolicy::Ieee).unwrap(), f64::INFINITY);
+        assert!(eval_str("ln(-1)", FloatPolicy::Ieee).unwrap().is_nan());
+        assert_eq!(
+            eval_str("-inf", FloatPolicy::Ieee).unwrap(),
+            f64::NEG_INFINITY
+        );
+        assert!(eval_str("nan + 1", FloatPolicy::Ieee).unwrap().is_nan());
+        assert_eq!(
+            strict_error("1/0"),
+            EvalError::DivisionByZero {
+                function: "division",
+                arguments: vec![1.0, 0.0]
+            }
+        );
+        assert_eq!(
+            strict_error("ln(-1)"),
+            EvalError::Domain {
+                function: "ln",
+                arguments: vec![-1.0]
+            }
+        );
+        assert_eq!(
+            strict_error("(-2)!"),
+            EvalError::Domain {
+                function: "factorial",
+                arguments: vec![-2.0]
+            }
+        );
+        assert!(matches!(strict_error("ln(0)"), EvalError::Domain { .. }));
+        assert!(matches!(
+            strict_error("0^-1"),
+            EvalError::DivisionByZero { .. }
+        ));
+        assert!(matches!(
+            strict_error("inf - inf"),
+            EvalError::Domain { .. }
+        ));
+        assert_eq!(
+            strict_error("exp(710)"),
+            EvalError::Overflow {
+                function: "exp",
+                arguments: vec![710.0]
+            }
+        );
+        assert_eq!(
+            strict_error("exp(710)").to_string(),
+            "exp overflows at 7.1e2"
+        );
+        assert_eq!(
+            strict_error("-2*1e308"),
+            EvalError::Overflow {
+                function: "multiplication",
+                arguments: vec![-2.0, 1e308]
+            }
+        );
+        assert_eq!(
+            strict_error("sum(1e308, 1e308)"),
+            EvalError::Overflow {
+                function: "sum",
+                arguments: vec![1e308, 1e308]
+            }
+        );
+        assert!(matches!(
+            strict_error("max([1, 2], 1e308) * 10"),
+            EvalError::Overflow { .. }
+        ));
+        for pole in [
+            "zeta(1)",
+            "digamma(-2)",
+            "gamma(0)",
+            "gamma(-1)",
+            "lgamma(-1)",
+            "beta(-1, 2)",
+            "log(2, 1)",
+            "artanh(1)",
+        ] {
+            assert!(
+                matches!(strict_error(pole), EvalError::Domain { .. }),
+                "{}",
+                pole
+            );
+        }
+        // Infinities and NaN given as arguments go through.
+        assert_eq!(
+            eval_str("exp(inf)", FloatPolicy::Strict).unwrap(),
+            f64::INFINITY
+        );
+        assert!(eval_str("sqrt(nan)", FloatPolicy::Strict).unwrap().is_nan());
+        assert_eq!(eval_str("1/inf", FloatPolicy::Strict).unwrap(), 0.0);
+        assert!(
+            (eval_str("integrate(exp(-x), x, 0, inf)", FloatPolicy::Strict).unwrap() - 1.0).abs()
+                < 1e-9
+        );
+    }
+    #[test]
+    fn test_eval_errors() {
+        use crate::calcparse::context::MAX_NESTED_EVALUATIONS;
+        use crate::calcparse::parser::Parser;
+
+        let eval_str = |expr, context: &mut Context| {
+            let ast = Parser::new(expr, None).unwrap().parse().unwrap();
+            eval_with(ast, context)
+        };
+        let mut context = Context::default();
+        assert_eq!(
+            eval_str("true == 3", &mut context),
+            Err(EvalError::TypeMismatch {
+                expected: "two numbers or two booleans",
+                got: "true and 3.0".to_string()
+            })
+        );
+        assert!(matches!(
+            eval_str("len(3)", &mut context),
+            Err(EvalError::TypeMismatch {
+                expected: "a list",
+                ..
+            })
+        ));
+        assert_eq!(
+            eval_str("npv(0.1, [])", &mut context),
+            Err(EvalError::ArityMismatch {
+                function: "npv",
+                expected: "at least 2".to_string(),
+                got: 1
+            })
+        );
+        assert_eq!(
+            eval_str("inv([[1, 2], [2, 4]])", &mut context),
+            Err(EvalError::SingularMatrix)
+        );
+        assert_eq!(
+            eval_str("var(1)", &mut context),
+            Err(EvalError::ArityMismatch {
+                function: "var",
+                expected: "at least 2".to_string(),
+                got: 1
+            })
+        );
+        for (expr, function, arguments) in [
+            ("nCr(4.5, 2)", "nCr", vec![4.5]),
+            ("fib(-1)", "fib", vec![-1.0]),
+            ("geomean(-1)", "geomean", vec![-1.0]),
+            ("normpdf(0, 0, -1)", "normpdf", vec![0.0, 0.0, -1.0]),
+        ] {
+            assert_eq!(
+                eval_str(expr, &mut context),
+                Err(EvalError::Domain {
+                    function,
+                    arguments
+                }),
+                "{}",
+                expr
+            );
+        }
+        assert_eq!(
+            eval_str("[1, 2][2]", &mut context),
+            Err(EvalError::IndexOutOfBounds {
+                index: 2.0,
+                length: 2
+            })
+        );
+        assert!(matches!(
+            eval_str("[1, 2] + [1, 2, 3]", &mut context),
+            Err(EvalError::DimensionMismatch(_))
+        ));
+        assert!(matches!(
+            eval_str("1..1e9", &mut context),
+            Err(EvalError::TooLarge { .. })
+        ));
+        let nested =
+            |depth| (0..depth).fold("1".to_string(), |body, _| format!("sum(i, 1, 1, {})", body));
+        // The outermost evaluation counts as one level.
+        let too_deep = nested(MAX_NESTED_EVALUATIONS);
+        assert_eq!(
+            eval_str(&too_deep, &mut context),
+            Err(EvalError::RecursionLimit(MAX_NESTED_EVALUATIONS))
+        );
+        let deepest = nested(MAX_NESTED_EVALUATIONS - 1);
+        assert_eq!(eval_str(&deepest, &mut context), Ok(Value::Number(1.0)));
+        context.integer_mode =
+            IntegerMode::from_width("i8", crate::calcparse::programmer::Overflow::Checked);
+        assert!(matches!(
+            eval_str("100 + 100", &mut context),
+            Err(EvalError::IntegerOverflow(_))
+        ));
+        assert!(matches!(
+            eval_str("1 / 0", &mut context),
+            Err(EvalError::DivisionByZero { .. })
+        ));
+    }
+    #[test]
+    fn test_unbound_variable() {
+        use crate::calcparse::parser::Parser;
+
+        let ast = Parser::new("sum(k, 1, 3, k) + k", None)
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert_eq!(
+            eval(ast),
+            Err(EvalError::UndefinedVariable {
+                name: "k".to_string(),
+                suggestion: None
+            })
+        );
+        let ast = Parser::new("sqr", None).unwrap().parse().unwrap();
+        assert_eq!(
+            eval(ast).unwrap_err().to_string(),
+            "Unknown variable sqr, did you mean `sqrt`? It takes 1 argument"
+        );
+    }
+    #[test]
+    #[allow(clippy::unnecessary_cast)]
     fn test_expr6() {
         use crate::calcparse::parser::Parser;
 
@@ -0,0 +1,124 @@
+/// A named constant, physical ones being given in SI units with their CODATA 2018 values.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Constant {
+    pub name: &'static str,
+    pub value: f64,
+    /// Empty for mathematical constants.
+    pub unit: &'static str,
+    pub description: &'static str,
+}
+
+const fn constant(
+    name: &'static str,
+    value: f64,
+    unit: &'static str,
+    description: &'static str,
+) -> Constant {
+    Constant {
+        name,
+        value,
+        unit,
+        description,
+    }
+}
+
+/// Constants an identifier refers to when no variable with the same name is bound, so that
+/// `sum(c, 1, 3, c)` still reads `c` as the index, and a variable the user defines named `c`
+/// or `h` hides the physical constant.
+pub const CONSTANTS: &[Constant] = &[
+    constant(
+        "pi",
+        std::f64::consts::PI,
+        "",
+        "Ratio of a circle's circumference to its diameter",
+    ),
+    constant("e", std::f64::consts::E, "", "Euler's number"),
+    constant(
+        "tau",
+        std::f64::consts::TAU,
+        "",
+        "Ratio of a circle's circumference to its radius, 2π",
+    ),
+    constant("phi", 1.618_033_988_749_895, "", "Golden ratio"),
+    constant(
+        "gamma_e",
+        0.577_215_664_901_532_9,
+        "",
+        "Euler–Mascheroni constant",
+    ),
+    constant("catalan", 0.915_965_594_177_219, "", "Catalan's constant"),
+    constant("c", 299_792_458.0, "m s^-1", "Speed of light in vacuum"),
+    constant("h", 6.626_070_15e-34, "J s", "Planck constant"),
+    constant("hbar", 1.054_571_817e-34, "J s", "Reduced Planck constant"),
+    constant(
+        "G",
+        6.674_30e-11,
+        "m^3 kg^-1 s^-2",
+        "Newtonian constant of gravitation",
+    ),
+    constant(
+        "g_n",
+        9.806_65,
+        "m s^-2",
+        "Standard acceleration of gravity",
+    ),
+    constant("k_B", 1.380_649e-23, "J K^-1", "Boltzmann constant"),
+    constant("N_A", 6.022_140_76e23, "mol^-1", "Avogadro constant"),
+    constant(
+        "R_gas",
+        8.314_462_618,
+        "J mol^-1 K^-1",
+        "Molar gas constant",
+    ),
+    constant("q_e", 1.602_176_634e-19, "C", "Elementary charge"),
+    constant("m_e", 9.109_383_701_5e-31, "kg", "Electron mass"),
+    constant("m_p", 1.672_621_923_69e-27, "kg", "Proton mass"),
+    constant(
+        "e0",
+        8.854_187_812_8e-12,
+        "F m^-1",
+        "Vacuum electric permittivity",
+    ),
+    constant(
+        "mu0",
+        1.256_637_062_12e-6,
+        "N A^-2",
+        "Vacuum magnetic permeability",
+    ),
+    constant(
+        "sigma_SB",
+        5.670_374_419e-8,
+        "W m^-2 K^-4",
+        "Stefan–Boltzmann constant",
+    ),
+    constant(
+        "alpha_fs",
+        7.297_352_569_3e-3,
+        "",
+        "Fine-structure constant",
+    ),
+];
+
+pub fn find(name: &str) -> Option<&'static Constant> {
+    CONSTANTS.iter().find(|constant| constant.name == name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_names_are_unique() {
+        for (i, constant) in CONSTANTS.iter().enumerate() {
+            assert_eq!(find(constant.name), Some(&CONSTANTS[i]));
+        }
+        assert!(find("x").is_none());
+    }
+    #[test]
+    fn test_derived_constants() {
+        let hbar = find("h").unwrap().value / std::f64::consts::TAU;
+        assert!((hbar / find("hbar").unwrap().value - 1.0).abs() < 1e-9);
+        let [e0, mu0, c] = ["e0", "mu0", "c"].map(|name| find(name).unwrap().value);
+        assert!((e0 * mu0 * c * c - 1.0).abs() < 1e-9);
+    }
+}
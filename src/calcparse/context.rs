@@ -0,0 +1,108 @@
+use super::error::EvalError;
+use super::parser::MAX_NESTING_DEPTH;
+use super::programmer::IntegerMode;
+use super::value::Value;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+/// How many evaluations can be nested, the outermost one included, numerical methods such as
+/// `sum` or `integrate` evaluating their expression from within it. Every level takes much
+/// more stack than a level of syntax, so this is well below the parser's nesting limit.
+pub const MAX_NESTED_EVALUATIONS: usize = 32;
+
+/// What the evaluator does when a computation gives an infinite or undefined result.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FloatPolicy {
+    /// Results follow IEEE 754, `1/0` being `inf` and `ln(-1)` being `NaN`.
+    #[default]
+    Ieee,
+    /// Divisions by zero, arguments out of a function's domain and overflows are errors, unless
+    /// an argument already is infinite or `NaN`.
+    Strict,
+}
+
+/// Settings the evaluator has to know about, kept alive by the REPL between expressions.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Context {
+    /// When set, arithmetic and bitwise operators work on fixed width integers.
+    pub integer_mode: Option<IntegerMode>,
+    /// Nesting limit handed to the parser.
+    pub max_depth: usize,
+    /// Whether infinite and undefined results are errors.
+    pub float_policy: FloatPolicy,
+    /// Relative tolerance used when comparing numbers, see `value::approx_eq`.
+    pub tolerance: f64,
+    /// Relative accuracy numerical methods such as `integrate` aim for.
+    pub precision: f64,
+    /// Sum of the error estimates of the integrals, derivatives and limits computed by the last
+    /// evaluation, if any. Only the outermost ones count, the error of an inner integral being
+    /// part of the function the outer one integrates.
+    pub estimated_error: Option<f64>,
+    /// Seed the random number generator was last reset with, it's seeded from the operating
+    /// system otherwise.
+    pub seed: Option<u64>,
+    rng: StdRng,
+    /// Variables in scope, the last binding of a name shadowing the previous ones.
+    variables: Vec<(String, Value)>,
+    /// Evaluations in progress, one within the other.
+    nested_evaluations: usize,
+}
+
+impl Default for Context {
+    fn default() -> Self {
+        Context {
+            integer_mode: None,
+            max_depth: MAX_NESTING_DEPTH,
+            float_policy: FloatPolicy::Ieee,
+            tolerance: 1e-12,
+            precision: 1e-10,
+            estimated_error: None,
+            seed: None,
+            rng: StdRng::from_entropy(),
+            variables: Vec::new(),
+            nested_evaluations: 0,
+        }
+    }
+}
+
+impl Context {
+    pub fn variable(&self, name: &str) -> Option<&Value> {
+        self.variables
+            .iter()
+            .rev()
+            .find(|(variable, _)| variable == name)
+            .map(|(_, value)| value)
+    }
+    /// Bind `name` until the matching `unbind`, shadowing any variable with the same name.
+    pub fn bind(&mut self, name: &str, value: Value) {
+        self.variables.push((name.to_string(), value));
+    }
+    pub fn unbind(&mut self) {
+        self.variables.pop();
+    }
+    /// Restart the random number generator from `seed`, so that the numbers drawn from then on
+    /// are always the same.
+    pub fn set_seed(&mut self, seed: u64) {
+        self.seed = Some(seed);
+        self.rng = StdRng::seed_from_u64(seed);
+    }
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.set_seed(seed);
+        self
+    }
+    pub fn rng(&mut self) -> &mut StdRng {
+        &mut self.rng
+    }
+    /// Start evaluating an expression from within another one, up to `MAX_NESTED_EVALUATIONS`
+    /// levels.
+    pub fn enter_evaluation(&mut self) -> Result<(), EvalError> {
+        if self.nested_evaluations >= MAX_NESTED_EVALUATIONS {
+            return Err(EvalError::RecursionLimit(MAX_NESTED_EVALUATIONS));
+        }
+        self.nested_evaluations += 1;
+        Ok(())
+    }
+    pub fn leave_evaluation(&mut self) {
+        self.nested_evaluations -= 1;
+    }
+}
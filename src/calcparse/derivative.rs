@@ -0,0 +1,133 @@
+use super::ast::Node;
+
+/// Deepest expression `derivative` walks through, anything deeper is left to numerical methods.
+const MAX_DERIVATIVE_DEPTH: usize = 256;
+
+/// Symbolic derivative of `node` with respect to `variable`, or `None` when it isn't known,
+/// for instance for `min`, `floor` or conditionals.
+pub fn derivative(node: &Node, variable: &str) -> Option<Node> {
+    derive(node, variable, 0)
+}
+
+fn derive(node: &Node, variable: &str, depth: usize) -> Option<Node> {
+    use self::Node::*;
+    if depth > MAX_DERIVATIVE_DEPTH {
+        return None;
+    }
+    if !node.depends_on(variable) {
+        return Some(Number(0.0));
+    }
+    let d = |expr: &Node| derive(expr, variable, depth + 1);
+    let derivative = match node {
+        Variable(_) => Number(1.0),
+        Add(u, v) => add(d(u)?, d(v)?),
+        Subtract(u, v) => sub(d(u)?, d(v)?),
+        Negative(u) => neg(d(u)?),
+        Multiply(u, v) => add(
+            mul(d(u)?, v.as_ref().clone()),
+            mul(u.as_ref().clone(), d(v)?),
+        ),
+        Divide(u, v) => div(
+            sub(
+                mul(d(u)?, v.as_ref().clone()),
+                mul(u.as_ref().clone(), d(v)?),
+            ),
+            mul(v.as_ref().clone(), v.as_ref().clone()),
+        ),
+        Caret(u, v) | Pow(u, v) if !v.depends_on(variable) => mul(
+            mul(
+                v.as_ref().clone(),
+                pow(u.as_ref().clone(), sub(v.as_ref().clone(), Number(1.0))),
+            ),
+            d(u)?,
+        ),
+        // d(u^v) = u^v * (v' ln(u) + v u' / u)
+        Caret(u, v) | Pow(u, v) => mul(
+            node.clone(),
+            add(
+                mul(d(v)?, Ln(Box::new(u.as_ref().clone()))),
+                div(mul(v.as_ref().clone(), d(u)?), u.as_ref().clone()),
+            ),
+        ),
+        Pow2(u) => mul(mul(Number(2.0), u.as_ref().clone()), d(u)?),
+        Pow3(u) => mul(mul(Number(3.0), Pow2(u.clone())), d(u)?),
+        Sqrt(u) => div(d(u)?, mul(Number(2.0), node.clone())),
+        Exp(u) => mul(node.clone(), d(u)?),
+        Exp2(u) => mul(mul(node.clone(), Number(std::f64::consts::LN_2)), d(u)?),
+        Ln(u) => div(d(u)?, u.as_ref().clone()),
+        Log(u, base) => d(&div(Ln(u.clone()), Ln(base.clone())))?,
+        Abs(u) => mul(Sign(u.clone()), d(u)?),
+        Sin(u) => mul(Cos(u.clone()), d(u)?),
+        Cos(u) => neg(mul(Sin(u.clone()), d(u)?)),
+        Tan(u) => div(d(u)?, Pow2(Box::new(Cos(u.clone())))),
+        Sinh(u) => mul(Cosh(u.clone()), d(u)?),
+        Cosh(u) => mul(Sinh(u.clone()), d(u)?),
+        Tanh(u) => div(d(u)?, Pow2(Box::new(Cosh(u.clone())))),
+        Asin(u) => div(d(u)?, Sqrt(Box::new(sub(Number(1.0), Pow2(u.clone()))))),
+        Acos(u) => neg(div(
+            d(u)?,
+            Sqrt(Box::new(sub(Number(1.0), Pow2(u.clone())))),
+        )),
+        Atan(u) => div(d(u)?, add(Number(1.0), Pow2(u.clone()))),
+        Arsinh(u) => div(d(u)?, Sqrt(Box::new(add(Pow2(u.clone()), Number(1.0))))),
+        Arcosh(u) => div(d(u)?, Sqrt(Box::new(sub(Pow2(u.clone()), Number(1.0))))),
+        Artanh(u) => div(d(u)?, sub(Number(1.0), Pow2(u.clone()))),
+        _ => return None,
+    };
+    Some(derivative)
+}
+
+fn add(u: Node, v: Node) -> Node {
+    Node::Add(Box::new(u), Box::new(v))
+}
+fn sub(u: Node, v: Node) -> Node {
+    Node::Subtract(Box::new(u), Box::new(v))
+}
+fn mul(u: Node, v: Node) -> Node {
+    Node::Multiply(Box::new(u), Box::new(v))
+}
+fn div(u: Node, v: Node) -> Node {
+    Node::Divide(Box::new(u), Box::new(v))
+}
+fn neg(u: Node) -> Node {
+    Node::Negative(Box::new(u))
+}
+fn pow(u: Node, v: Node) -> Node {
+    Node::Caret(Box::new(u), Box::new(v))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calcparse::ast::eval_with;
+    use crate::calcparse::context::Context;
+    use crate::calcparse::parser::Parser;
+    use crate::calcparse::value::Value;
+
+    fn derivative_at(expr: &str, x: f64) -> Option<f64> {
+        let ast = Parser::new(expr, None).unwrap().parse().unwrap();
+        let mut context = Context::default();
+        context.bind("x", Value::Number(x));
+        let value = eval_with(derivative(&ast, "x")?, &mut context).unwrap();
+        Some(value.as_number().unwrap())
+    }
+
+    #[test]
+    fn test_derivatives() {
+        assert_eq!(derivative_at("x^3 + 2x", 2.0), Some(14.0));
+        assert_eq!(derivative_at("5", 2.0), Some(0.0));
+        let value = derivative_at("sin(x) ln(x) + x^x", 2.0).unwrap();
+        let expected = 2f64.cos() * 2f64.ln() + 2f64.sin() / 2.0 + 4.0 * (2f64.ln() + 1.0);
+        assert!((value - expected).abs() < 1e-12);
+        assert!(
+            (derivative_at("atan(x²)/sqrt(x)", 1.0).unwrap() - 1.0 + std::f64::consts::PI / 8.0)
+                .abs()
+                < 1e-12
+        );
+    }
+    #[test]
+    fn test_unknown_derivative() {
+        assert_eq!(derivative_at("floor(x)", 2.0), None);
+        assert_eq!(derivative_at("floor(2) + sum(x, 1, 3, x)", 2.0), Some(0.0));
+    }
+}
@@ -0,0 +1,257 @@
+use super::error::EvalError;
+use super::numeric;
+use super::token::NativeFunction;
+use rand::rngs::StdRng;
+use statrs::distribution::{
+    Beta, Binomial, ChiSquared, Continuous, ContinuousCDF, Discrete, DiscreteCDF, Exp,
+    FisherSnedecor, Gamma, Normal, Poisson, StudentsT,
+};
+
+/// Probability distributions the calculator knows about.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Distribution {
+    Normal,
+    StudentsT,
+    ChiSquared,
+    FisherSnedecor,
+    Exponential,
+    Beta,
+    Gamma,
+    Binomial,
+    Poisson,
+}
+
+/// What is computed from a distribution at a given point.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DistributionFunction {
+    /// Probability density, or probability mass for discrete distributions.
+    Density,
+    Cumulative,
+    /// Inverse of the cumulative distribution function, at a probability.
+    Inverse,
+}
+
+/// Draw a random number, `rand::distributions::Distribution::sample` being generic over the
+/// generator it can't be called on a trait object.
+trait Sample {
+    fn draw(&self, rng: &mut StdRng) -> f64;
+}
+impl<T: rand::distributions::Distribution<f64>> Sample for T {
+    fn draw(&self, rng: &mut StdRng) -> f64 {
+        self.sample(rng)
+    }
+}
+
+trait ContinuousDistribution: Continuous<f64, f64> + ContinuousCDF<f64, f64> + Sample {}
+impl<T: Continuous<f64, f64> + ContinuousCDF<f64, f64> + Sample> ContinuousDistribution for T {}
+
+trait DiscreteDistribution: Discrete<u64, f64> + DiscreteCDF<u64, f64> + Sample {}
+impl<T: Discrete<u64, f64> + DiscreteCDF<u64, f64> + Sample> DiscreteDistribution for T {}
+
+impl Distribution {
+    /// Number of parameters following the point the distribution is evaluated at.
+    pub fn parameter_count(&self) -> usize {
+        use self::Distribution::*;
+        match self {
+            StudentsT | ChiSquared | Exponential | Poisson => 1,
+            Normal | FisherSnedecor | Beta | Gamma | Binomial => 2,
+        }
+    }
+    fn name(&self) -> &'static str {
+        use self::Distribution::*;
+        match self {
+            Normal => "normal",
+            StudentsT => "Student's t",
+            ChiSquared => "chi-squared",
+            FisherSnedecor => "F",
+            Exponential => "exponential",
+            Beta => "beta",
+            Gamma => "gamma",
+            Binomial => "binomial",
+            Poisson => "Poisson",
+        }
+    }
+    /// The distribution with `parameters`, if it is continuous, `invalid` giving the error for
+    /// parameters out of range.
+    fn continuous(
+        &self,
+        parameters: &[f64],
+        invalid: impl Fn() -> EvalError,
+    ) -> Result<Option<Box<dyn ContinuousDistribution>>, EvalError> {
+        let invalid = |_| invalid();
+        let distribution: Box<dyn ContinuousDistribution> = match self {
+            Distribution::Normal => {
+                Box::new(Normal::new(parameters[0], parameters[1]).map_err(invalid)?)
+            }
+            Distribution::StudentsT => {
+                Box::new(StudentsT::new(0.0, 1.0, parameters[0]).map_err(invalid)?)
+            }
+            Distribution::ChiSquared => Box::new(ChiSquared::new(parameters[0]).map_err(invalid)?),
+            Distribution::FisherSnedecor => {
+                Box::new(FisherSnedecor::new(parameters[0], parameters[1]).map_err(invalid)?)
+            }
+            Distribution::Exponential => Box::new(Exp::new(parameters[0]).map_err(invalid)?),
+            Distribution::Beta => {
+                Box::new(Beta::new(parameters[0], parameters[1]).map_err(invalid)?)
+            }
+            Distribution::Gamma => {
+                Box::new(Gamma::new(parameters[0], parameters[1]).map_err(invalid)?)
+            }
+            Distribution::Binomial | Distribution::Poisson => return Ok(None),
+        };
+        Ok(Some(distribution))
+    }
+    fn discrete(
+        &self,
+        parameters: &[f64],
+        invalid: impl Fn() -> EvalError,
+    ) -> Result<Box<dyn DiscreteDistribution>, EvalError> {
+        match self {
+            Distribution::Binomial => {
+                let trials = parameters[0];
+                if trials.fract() != 0.0 || trials < 0.0 {
+                    return Err(invalid());
+                }
+                let binomial = Binomial::new(parameters[1], trials as u64);
+                Ok(Box::new(binomial.map_err(|_| invalid())?))
+            }
+            _ => Ok(Box::new(
+                Poisson::new(parameters[0]).map_err(|_| invalid())?,
+            )),
+        }
+    }
+}
+
+/// Evaluate `function` of `distribution` at `args[0]`, the parameters of the distribution
+/// following it. Inverses not known in closed form are computed with Brent's method, aiming
+/// at a relative error of `precision`.
+pub fn evaluate(
+    distribution: Distribution,
+    function: DistributionFunction,
+    args: &[f64],
+    precision: f64,
+) -> Result<f64, EvalError> {
+    let (x, parameters) = (args[0], &args[1..]);
+    let domain = || EvalError::Domain {
+        function: NativeFunction::Distribution(distribution, function).name(),
+        arguments: args.to_vec(),
+    };
+    if x.is_nan() || function == DistributionFunction::Inverse && !(0.0..=1.0).contains(&x) {
+        return Err(domain());
+    }
+    let Some(continuous) = distribution.continuous(parameters, domain)? else {
+        let discrete = distribution.discrete(parameters, domain)?;
+        return match function {
+            DistributionFunction::Density if x.fract() != 0.0 => Err(domain()),
+            _ if x < 0.0 => Ok(0.0),
+            DistributionFunction::Density => Ok(discrete.pmf(x as u64)),
+            DistributionFunction::Cumulative => Ok(discrete.cdf(x.floor() as u64)),
+            DistributionFunction::Inverse => Err(EvalError::InvalidArgument(format!(
+                "The inverse of the {} distribution isn't available",
+                distribution.name()
+            ))),
+        };
+    };
+    match function {
+        DistributionFunction::Density => Ok(continuous.pdf(x)),
+        DistributionFunction::Cumulative => Ok(continuous.cdf(x)),
+        DistributionFunction::Inverse => match distribution {
+            Distribution::Normal | Distribution::StudentsT => Ok(continuous.inverse_cdf(x)),
+            _ => inverse(continuous.as_ref(), x, precision),
+        },
+    }
+}
+
+/// Draw a random number following `distribution`, given its parameters.
+pub fn sample(
+    distribution: Distribution,
+    parameters: &[f64],
+    rng: &mut StdRng,
+) -> Result<f64, EvalError> {
+    let domain = || EvalError::Domain {
+        function: NativeFunction::Sample(distribution).name(),
+        arguments: parameters.to_vec(),
+    };
+    match distribution.continuous(parameters, domain)? {
+        Some(continuous) => Ok(continuous.draw(rng)),
+        None => Ok(distribution.discrete(parameters, domain)?.draw(rng)),
+    }
+}
+
+/// Inverse of the cumulative distribution function of a distribution supported by the positive
+/// numbers, found by bracketing `p` then refining the bracket.
+fn inverse(
+    distribution: &dyn ContinuousDistribution,
+    p: f64,
+    precision: f64,
+) -> Result<f64, EvalError> {
+    let (low, high) = (distribution.min(), distribution.max());
+    if p == 0.0 {
+        return Ok(low);
+    }
+    if p == 1.0 {
+        return Ok(high);
+    }
+    let mut upper = high.min(1.0);
+    while distribution.cdf(upper) < p {
+        upper *= 2.0;
+        if upper.is_infinite() {
+            return Ok(high);
+        }
+    }
+    numeric::brent(&mut |x| Ok(distribution.cdf(x) - p), low, upper, precision)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use DistributionFunction::*;
+
+    #[test]
+    fn test_continuous() {
+        let p = evaluate(Distribution::Normal, Cumulative, &[1.96, 0.0, 1.0], 1e-12).unwrap();
+        assert!((p - 0.975).abs() < 1e-4);
+        let x = evaluate(Distribution::Normal, Inverse, &[0.975, 0.0, 1.0], 1e-12).unwrap();
+        assert!((x - 1.959_963_984_540_054).abs() < 1e-9);
+        let x = evaluate(Distribution::ChiSquared, Inverse, &[0.95, 3.0], 1e-12).unwrap();
+        assert!((x - 7.814_727_903_251_178).abs() < 1e-9);
+        let x = evaluate(Distribution::Beta, Inverse, &[0.5, 2.0, 2.0], 1e-12).unwrap();
+        assert!((x - 0.5).abs() < 1e-12);
+        let density = evaluate(Distribution::Exponential, Density, &[0.0, 2.0], 1e-12).unwrap();
+        assert_eq!(density, 2.0);
+    }
+    #[test]
+    fn test_discrete() {
+        let p = evaluate(Distribution::Binomial, Density, &[2.0, 4.0, 0.5], 1e-12).unwrap();
+        assert!((p - 0.375).abs() < 1e-12);
+        let p = evaluate(Distribution::Poisson, Cumulative, &[1.5, 2.0], 1e-12).unwrap();
+        assert!((p - 3.0 * (-2f64).exp()).abs() < 1e-12);
+        assert_eq!(
+            evaluate(Distribution::Poisson, Cumulative, &[-1.0, 2.0], 1e-12).unwrap(),
+            0.0
+        );
+        assert!(evaluate(Distribution::Binomial, Density, &[2.5, 4.0, 0.5], 1e-12).is_err());
+    }
+    #[test]
+    fn test_sample() {
+        use rand::SeedableRng;
+
+        let mut rng = StdRng::seed_from_u64(7);
+        let samples = (0..10_000)
+            .map(|_| sample(Distribution::Normal, &[5.0, 2.0], &mut rng).unwrap())
+            .collect::<Vec<_>>();
+        let mean = samples.iter().sum::<f64>() / samples.len() as f64;
+        assert!((mean - 5.0).abs() < 0.1);
+        let k = sample(Distribution::Poisson, &[3.0], &mut rng).unwrap();
+        assert!(k >= 0.0 && k.fract() == 0.0);
+        assert!(sample(Distribution::Beta, &[0.0, 1.0], &mut rng).is_err());
+    }
+    #[test]
+    fn test_invalid_parameters() {
+        assert!(evaluate(Distribution::Normal, Density, &[0.0, 0.0, -1.0], 1e-12).is_err());
+        assert!(evaluate(Distribution::Binomial, Density, &[1.0, 2.5, 0.5], 1e-12).is_err());
+        assert!(evaluate(Distribution::Binomial, Density, &[1.0, 2.0, 1.5], 1e-12).is_err());
+        assert!(evaluate(Distribution::Normal, Inverse, &[1.5, 0.0, 1.0], 1e-12).is_err());
+        assert!(evaluate(Distribution::StudentsT, Cumulative, &[f64::NAN, 3.0], 1e-12).is_err());
+    }
+}
@@ -0,0 +1,140 @@
+use super::programmer::IntegerMode;
+use super::suggestion::{self, Suggestion};
+use std::error;
+use std::fmt;
+
+/// Why an expression that parsed fine couldn't be evaluated.
+#[derive(Debug, Clone, PartialEq)]
+pub enum EvalError {
+    DivisionByZero {
+        function: &'static str,
+        arguments: Vec<f64>,
+    },
+    /// The arguments are out of the function's domain, or at one of its poles such as `ln(0)`.
+    Domain {
+        function: &'static str,
+        arguments: Vec<f64>,
+    },
+    /// The result is finite but too large to be represented.
+    Overflow {
+        function: &'static str,
+        arguments: Vec<f64>,
+    },
+    /// A result out of the range of the programmer mode's integers, when they don't wrap.
+    IntegerOverflow(IntegerMode),
+    UndefinedVariable {
+        name: String,
+        /// A function or a constant with a close name, in case of a typo.
+        suggestion: Option<Suggestion>,
+    },
+    ArityMismatch {
+        function: &'static str,
+        /// Such as `3`, `3 or 4` or `at least 2`.
+        expected: String,
+        got: usize,
+    },
+    /// Numerical methods nested deeper than the nesting limit, each one evaluating its
+    /// expression from within the other.
+    RecursionLimit(usize),
+    TypeMismatch {
+        expected: &'static str,
+        got: String,
+    },
+    /// A numerical method, such as a root finding or a limit, that didn't settle on a value.
+    NoConvergence(String),
+    /// Lists or matrices whose shapes don't fit together, such as a product of a 2×3 and a
+    /// 2×2 matrix.
+    DimensionMismatch(String),
+    IndexOutOfBounds {
+        index: f64,
+        length: usize,
+    },
+    /// A size, such as the length of a range or the degree of a polynomial, over its limit.
+    TooLarge {
+        what: &'static str,
+        limit: usize,
+    },
+    SingularMatrix,
+    /// An expression that isn't a polynomial in the variable named.
+    NotPolynomial(String),
+    /// The expression has syntax errors, reported when parsing it.
+    SyntaxError,
+    /// Any other argument a function can't work with, along with the reason.
+    InvalidArgument(String),
+}
+
+impl fmt::Display for EvalError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let list = |arguments: &[f64]| {
+            arguments
+                .iter()
+                .map(|x| format!("{:e}", x))
+                .collect::<Vec<_>>()
+                .join(" and ")
+        };
+        match self {
+            EvalError::DivisionByZero {
+                function,
+                arguments,
+            } => write!(f, "Division by zero in {} of {}", function, list(arguments)),
+            EvalError::Domain {
+                function,
+                arguments,
+            } => write!(f, "{} is undefined at {}", function, list(arguments)),
+            EvalError::Overflow {
+                function,
+                arguments,
+            } => write!(f, "{} overflows at {}", function, list(arguments)),
+            EvalError::IntegerOverflow(mode) => write!(f, "Integer overflow in {}", mode),
+            EvalError::UndefinedVariable { name, suggestion } => {
+                write!(f, "Unknown variable {}", name)?;
+                match suggestion {
+                    Some(suggestion) => write!(f, ", {}", suggestion),
+                    None => Ok(()),
+                }
+            }
+            EvalError::ArityMismatch {
+                function,
+                expected,
+                got,
+            } => write!(
+                f,
+                "The {} function expects {} arguments, got {}",
+                function, expected, got
+            ),
+            EvalError::RecursionLimit(depth) => {
+                write!(f, "Evaluation nested deeper than {} levels", depth)
+            }
+            EvalError::TypeMismatch { expected, got } => {
+                write!(f, "Expected {}, got {}", expected, got)
+            }
+            EvalError::NoConvergence(method) => write!(f, "{} did not converge", method),
+            EvalError::DimensionMismatch(reason) => write!(f, "Dimension mismatch: {}", reason),
+            EvalError::IndexOutOfBounds { index, length } => write!(
+                f,
+                "Index {} is out of bounds for a list of length {}",
+                index, length
+            ),
+            EvalError::TooLarge { what, limit } => {
+                write!(f, "The {} is limited to {}", what, limit)
+            }
+            EvalError::SingularMatrix => write!(f, "The matrix is singular"),
+            EvalError::NotPolynomial(variable) => {
+                write!(f, "The expression is not a polynomial in {}", variable)
+            }
+            EvalError::SyntaxError => write!(f, "The expression has syntax errors"),
+            EvalError::InvalidArgument(reason) => write!(f, "{}", reason),
+        }
+    }
+}
+
+impl EvalError {
+    pub fn undefined_variable(name: &str) -> Self {
+        EvalError::UndefinedVariable {
+            name: name.to_string(),
+            suggestion: suggestion::suggest(name),
+        }
+    }
+}
+
+impl error::Error for EvalError {}
@@ -0,0 +1,314 @@
+use super::error::EvalError;
+use super::matrix::{self, Matrix};
+use super::numeric;
+use std::ops::RangeInclusive;
+
+/// Time value of money functions. Money received is positive and money paid negative, as in
+/// spreadsheets, payments happening at the end of each period.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Financial {
+    /// `npv(rate, cf...)`, the first cash flow happening now.
+    NetPresentValue,
+    /// `irr(cf...)`, the rate at which the net present value is zero.
+    InternalRateOfReturn,
+    /// `pmt(rate, n, pv, [fv])`
+    Payment,
+    /// `fv(rate, n, pmt, [pv])`
+    FutureValue,
+    /// `pv(rate, n, pmt, [fv])`
+    PresentValue,
+    /// `nper(rate, pmt, pv, [fv])`
+    Periods,
+    /// `rate(n, pmt, pv, [fv])`
+    Rate,
+    /// `amortize(rate, n, pv)`, the repayment schedule of a loan.
+    Amortize,
+}
+
+/// Columns of the schedule built by `amortize`.
+pub const AMORTIZATION_COLUMNS: [&str; 5] =
+    ["Period", "Payment", "Interest", "Principal", "Balance"];
+
+impl Financial {
+    /// How many numbers the function takes.
+    pub fn arguments(&self) -> RangeInclusive<usize> {
+        use self::Financial::*;
+        match self {
+            NetPresentValue => 2..=usize::MAX,
+            InternalRateOfReturn => 2..=usize::MAX,
+            Payment | FutureValue | PresentValue | Periods | Rate => 3..=4,
+            Amortize => 3..=3,
+        }
+    }
+    pub fn name(&self) -> &'static str {
+        use self::Financial::*;
+        match self {
+            NetPresentValue => "npv",
+            InternalRateOfReturn => "irr",
+            Payment => "pmt",
+            FutureValue => "fv",
+            PresentValue => "pv",
+            Periods => "nper",
+            Rate => "rate",
+            Amortize => "amortize",
+        }
+    }
+    /// Position of the first cash flow of the functions taking any number of them, whose lists
+    /// are looked through. The other functions apply element-wise to lists.
+    pub fn cash_flows(&self) -> Option<usize> {
+        match self {
+            Financial::NetPresentValue => Some(1),
+            Financial::InternalRateOfReturn => Some(0),
+            _ => None,
+        }
+    }
+    /// Reject a wrong number of arguments, once lists of cash flows have been flattened.
+    pub fn check_arguments(&self, count: usize) -> Result<(), EvalError> {
+        let expected = self.arguments();
+        if expected.contains(&count) {
+            return Ok(());
+        }
+        let expected = match (expected.start(), expected.end()) {
+            (start, &usize::MAX) => format!("at least {}", start),
+            (start, end) if start == end => start.to_string(),
+            (start, end) => format!("{} or {}", start, end),
+        };
+        Err(EvalError::ArityMismatch {
+            function: self.name(),
+            expected,
+            got: count,
+        })
+    }
+}
+
+/// The error for arguments of `function` out of its domain.
+fn domain(function: Financial, arguments: &[f64]) -> EvalError {
+    EvalError::Domain {
+        function: function.name(),
+        arguments: arguments.to_vec(),
+    }
+}
+
+fn check_rate(rate: f64, function: Financial) -> Result<(), EvalError> {
+    if rate.is_nan() || rate <= -1.0 {
+        return Err(domain(function, &[rate]));
+    }
+    Ok(())
+}
+
+/// `((1 + rate)^n - 1) / rate`, what a payment of 1 each period is worth after `n` of them.
+fn annuity_factor(rate: f64, n: f64) -> f64 {
+    if rate == 0.0 {
+        n
+    } else {
+        (n * rate.ln_1p()).exp_m1() / rate
+    }
+}
+
+/// Value of the whole operation at the end, which is zero when `rate` matches the other terms.
+fn balance(rate: f64, n: f64, pmt: f64, pv: f64, fv: f64) -> f64 {
+    pv * (n * rate.ln_1p()).exp() + pmt * annuity_factor(rate, n) + fv
+}
+
+/// The payment each period repaying `pv` down to `-fv` over `n` periods.
+fn payment(function: Financial, rate: f64, n: f64, pv: f64, fv: f64) -> Result<f64, EvalError> {
+    check_rate(rate, function)?;
+    if n == 0.0 {
+        return Err(domain(function, &[n]));
+    }
+    Ok(-(pv * (n * rate.ln_1p()).exp() + fv) / annuity_factor(rate, n))
+}
+
+/// Net present value of cash flows one period apart.
+fn net_present_value(rate: f64, cash_flows: &[f64]) -> f64 {
+    let discount = 1.0 / (1.0 + rate);
+    cash_flows
+        .iter()
+        .rev()
+        .fold(0.0, |total, cash_flow| total * discount + cash_flow)
+}
+
+/// A rate for which `f` is zero, looking for a sign change from 0 upward then toward -1.
+fn solve_rate(f: &mut dyn FnMut(f64) -> f64, precision: f64) -> Result<f64, EvalError> {
+    let start = f(0.0);
+    if start == 0.0 {
+        return Ok(0.0);
+    }
+    for towards_minus_one in [false, true] {
+        let mut previous = 0.0;
+        for k in 1..numeric::MAX_ITERATIONS {
+            let rate = if towards_minus_one {
+                (-(k as f64)).exp2() - 1.0
+            } else {
+                (k as f64).exp2() - 1.0
+            };
+            let value = f(rate);
+            if value.is_nan() || rate <= -1.0 || rate.is_infinite() {
+                break;
+            }
+            if (value > 0.0) != (start > 0.0) {
+                let (a, b) = if rate < previous {
+                    (rate, previous)
+                } else {
+                    (previous, rate)
+                };
+                return numeric::brent(&mut |rate| Ok(f(rate)), a, b, precision);
+            }
+            previous = rate;
+        }
+    }
+    Err(EvalError::NoConvergence("Solving for the rate".to_string()))
+}
+
+/// The repayment schedule of a loan of `pv` over `n` periods, one row per period. The payment
+/// and its interest and principal parts are paid, so negative like for `pmt`, and the balance
+/// is what remains to be repaid.
+pub fn amortize(rate: f64, n: f64, pv: f64) -> Result<Matrix, EvalError> {
+    if n.fract() != 0.0 || n < 1.0 {
+        return Err(domain(Financial::Amortize, &[n]));
+    }
+    if n > matrix::MAX_DIMENSION as f64 {
+        return Err(EvalError::TooLarge {
+            what: "number of periods of a schedule",
+            limit: matrix::MAX_DIMENSION,
+        });
+    }
+    let payment = payment(Financial::Amortize, rate, n, pv, 0.0)?;
+    let mut remaining = pv;
+    let rows = (1..=n as usize)
+        .map(|period| {
+            let interest = -remaining * rate;
+            let principal = payment - interest;
+            remaining += principal;
+            if period == n as usize {
+                // Only rounding errors are left.
+                remaining = 0.0;
+            }
+            vec![period as f64, payment, interest, principal, remaining]
+        })
+        .collect();
+    Matrix::from_rows(rows).ok_or_else(|| domain(Financial::Amortize, &[n]))
+}
+
+/// Evaluate `function`, root finding aiming at a relative error of `precision`.
+pub fn evaluate(function: Financial, args: &[f64], precision: f64) -> Result<f64, EvalError> {
+    use self::Financial::*;
+    function.check_arguments(args.len())?;
+    let last = |default| args.get(3).copied().unwrap_or(default);
+    match function {
+        NetPresentValue => {
+            check_rate(args[0], function)?;
+            Ok(net_present_value(args[0], &args[1..]))
+        }
+        InternalRateOfReturn => {
+            if !args.iter().any(|x| *x > 0.0) || !args.iter().any(|x| *x < 0.0) {
+                return Err(domain(function, args));
+            }
+            solve_rate(&mut |rate| net_present_value(rate, args), precision)
+        }
+        Payment => payment(function, args[0], args[1], args[2], last(0.0)),
+        FutureValue => {
+            let (rate, n, pmt, pv) = (args[0], args[1], args[2], last(0.0));
+            check_rate(rate, function)?;
+            Ok(-balance(rate, n, pmt, pv, 0.0))
+        }
+        PresentValue => {
+            let (rate, n, pmt, fv) = (args[0], args[1], args[2], last(0.0));
+            check_rate(rate, function)?;
+            Ok(-(pmt * annuity_factor(rate, n) + fv) / (n * rate.ln_1p()).exp())
+        }
+        Periods => {
+            let (rate, pmt, pv, fv) = (args[0], args[1], args[2], last(0.0));
+            check_rate(rate, function)?;
+            let n = if rate == 0.0 {
+                -(pv + fv) / pmt
+            } else {
+                ((pmt - fv * rate) / (pmt + pv * rate)).ln() / rate.ln_1p()
+            };
+            if !n.is_finite() {
+                return Err(domain(function, args));
+            }
+            Ok(n)
+        }
+        Rate => {
+            let (n, pmt, pv, fv) = (args[0], args[1], args[2], last(0.0));
+            solve_rate(&mut |rate| balance(rate, n, pmt, pv, fv), precision)
+        }
+        Amortize => Err(EvalError::TypeMismatch {
+            expected: "a number",
+            got: "the schedule of amortize".to_string(),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use Financial::*;
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() <= 1e-9 * expected.abs().max(1.0),
+            "{} != {}",
+            actual,
+            expected
+        );
+    }
+    #[test]
+    fn test_time_value_of_money() {
+        let rate = 0.05 / 12.0;
+        let pmt = evaluate(Payment, &[rate, 360.0, 200_000.0], 1e-12).unwrap();
+        assert_close(pmt, -1_073.643_246_024_277_7);
+        assert_close(
+            evaluate(PresentValue, &[rate, 360.0, pmt], 1e-12).unwrap(),
+            200_000.0,
+        );
+        assert_close(
+            evaluate(Periods, &[rate, pmt, 200_000.0], 1e-12).unwrap(),
+            360.0,
+        );
+        assert_close(
+            evaluate(Rate, &[360.0, pmt, 200_000.0], 1e-14).unwrap(),
+            rate,
+        );
+        assert_close(
+            evaluate(FutureValue, &[0.1, 2.0, 0.0, -100.0], 1e-12).unwrap(),
+            121.0,
+        );
+        assert_close(
+            evaluate(Payment, &[0.0, 10.0, 1000.0, 0.0], 1e-12).unwrap(),
+            -100.0,
+        );
+        assert!(evaluate(Payment, &[-1.5, 10.0, 1000.0], 1e-12).is_err());
+        assert!(evaluate(Payment, &[0.1, 10.0], 1e-12).is_err());
+    }
+    #[test]
+    fn test_cash_flows() {
+        assert_close(
+            evaluate(NetPresentValue, &[0.1, -100.0, 55.0, 60.5], 1e-12).unwrap(),
+            0.0,
+        );
+        assert_close(
+            evaluate(InternalRateOfReturn, &[-100.0, 55.0, 60.5], 1e-14).unwrap(),
+            0.1,
+        );
+        assert_close(
+            evaluate(InternalRateOfReturn, &[-100.0, 30.0, 30.0, 30.0], 1e-14).unwrap(),
+            -0.050_885_441_372_620_606,
+        );
+        assert!(evaluate(InternalRateOfReturn, &[100.0, 30.0], 1e-12).is_err());
+    }
+    #[test]
+    fn test_amortize() {
+        let schedule = amortize(0.1, 2.0, 1000.0).unwrap();
+        assert_eq!((schedule.rows(), schedule.columns()), (2, 5));
+        let payment = evaluate(Payment, &[0.1, 2.0, 1000.0], 1e-12).unwrap();
+        assert_close(payment, -1000.0 * 1.21 / 2.1);
+        assert_close(schedule.row(0)[1], payment);
+        assert_close(schedule.row(0)[2], -100.0);
+        assert_close(schedule.row(0)[3], payment + 100.0);
+        assert_close(schedule.row(0)[4], 1000.0 + payment + 100.0);
+        assert_close(schedule.row(1)[4], 0.0);
+        assert!(amortize(0.1, 2.5, 1000.0).is_err());
+    }
+}
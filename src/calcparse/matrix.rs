@@ -0,0 +1,647 @@
+use super::error::EvalError;
+use num_complex::Complex64;
+use std::fmt;
+
+/// Largest number of rows or columns `identity` builds.
+pub const MAX_DIMENSION: usize = 1000;
+/// Sweeps over every element off the diagonal after which Jacobi's method gives up, it
+/// usually takes less than 10.
+const MAX_JACOBI_SWEEPS: usize = 50;
+/// QR iterations after which an eigenvalue of a Hessenberg matrix is taken as not converging.
+const MAX_QR_ITERATIONS: usize = 30;
+
+/// A dense matrix of real numbers, stored row by row.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Matrix {
+    rows: usize,
+    columns: usize,
+    elements: Vec<f64>,
+}
+
+impl Matrix {
+    /// A matrix from its rows, which must all have the same, non zero, length.
+    pub fn from_rows(rows: Vec<Vec<f64>>) -> Option<Self> {
+        let columns = rows.first()?.len();
+        if columns == 0 || rows.iter().any(|row| row.len() != columns) {
+            return None;
+        }
+        Some(Matrix {
+            rows: rows.len(),
+            columns,
+            elements: rows.concat(),
+        })
+    }
+    /// A matrix with a single column.
+    pub fn column(elements: Vec<f64>) -> Self {
+        Matrix {
+            rows: elements.len(),
+            columns: 1,
+            elements,
+        }
+    }
+    /// A matrix with a single row.
+    pub fn row_vector(elements: Vec<f64>) -> Self {
+        Matrix {
+            rows: 1,
+            columns: elements.len(),
+            elements,
+        }
+    }
+    fn zeros(rows: usize, columns: usize) -> Self {
+        Matrix {
+            rows,
+            columns,
+            elements: vec![0.0; rows * columns],
+        }
+    }
+    pub fn identity(size: usize) -> Self {
+        let mut identity = Matrix::zeros(size, size);
+        for i in 0..size {
+            identity.elements[i * size + i] = 1.0;
+        }
+        identity
+    }
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+    pub fn columns(&self) -> usize {
+        self.columns
+    }
+    pub fn row(&self, i: usize) -> &[f64] {
+        &self.elements[i * self.columns..(i + 1) * self.columns]
+    }
+    pub fn elements(&self) -> &[f64] {
+        &self.elements
+    }
+    pub fn into_elements(self) -> Vec<f64> {
+        self.elements
+    }
+    fn get(&self, i: usize, j: usize) -> f64 {
+        self.elements[i * self.columns + j]
+    }
+    fn swap_rows(&mut self, i: usize, k: usize) {
+        for j in 0..self.columns {
+            self.elements
+                .swap(i * self.columns + j, k * self.columns + j);
+        }
+    }
+    fn is_square(&self) -> Result<usize, EvalError> {
+        if self.rows == self.columns {
+            Ok(self.rows)
+        } else {
+            Err(EvalError::DimensionMismatch(format!(
+                "expected a square matrix, got a {}x{} one",
+                self.rows, self.columns
+            )))
+        }
+    }
+    pub fn transpose(&self) -> Self {
+        let mut elements = Vec::with_capacity(self.elements.len());
+        for j in 0..self.columns {
+            for i in 0..self.rows {
+                elements.push(self.get(i, j));
+            }
+        }
+        Matrix {
+            rows: self.columns,
+            columns: self.rows,
+            elements,
+        }
+    }
+    pub fn mul(&self, other: &Matrix) -> Result<Self, EvalError> {
+        if self.columns != other.rows {
+            return Err(EvalError::DimensionMismatch(format!(
+                "cannot multiply a {}x{} matrix by a {}x{} one",
+                self.rows, self.columns, other.rows, other.columns
+            )));
+        }
+        let mut elements = vec![0.0; self.rows * other.columns];
+        for i in 0..self.rows {
+            for k in 0..self.columns {
+                let a = self.get(i, k);
+                for j in 0..other.columns {
+                    elements[i * other.columns + j] += a * other.get(k, j);
+                }
+            }
+        }
+        Ok(Matrix {
+            rows: self.rows,
+            columns: other.columns,
+            elements,
+        })
+    }
+    pub fn trace(&self) -> Result<f64, EvalError> {
+        let size = self.is_square()?;
+        Ok((0..size).map(|i| self.get(i, i)).sum())
+    }
+    /// Reduce the matrix to row echelon form with partial pivoting, along with `other` which
+    /// goes through the same row operations. Pivots at most `tolerance` times the largest
+    /// element are taken as zero. Gives the columns of the pivots and the sign of the
+    /// permutation of the rows.
+    fn eliminate(&mut self, other: &mut Matrix, tolerance: f64) -> (Vec<usize>, f64) {
+        let threshold = tolerance * self.elements.iter().fold(0.0, |max, x| x.abs().max(max));
+        let mut pivots = Vec::new();
+        let mut sign = 1.0;
+        for j in 0..self.columns {
+            let row = pivots.len();
+            if row == self.rows {
+                break;
+            }
+            let (pivot, value) = (row..self.rows).map(|i| (i, self.get(i, j).abs())).fold(
+                (row, 0.0),
+                |best, (i, value)| {
+                    if value > best.1 {
+                        (i, value)
+                    } else {
+                        best
+                    }
+                },
+            );
+            if value <= threshold {
+                continue;
+            }
+            if pivot != row {
+                self.swap_rows(pivot, row);
+                other.swap_rows(pivot, row);
+                sign = -sign;
+            }
+            for i in (row + 1)..self.rows {
+                let factor = self.get(i, j) / self.get(row, j);
+                if factor == 0.0 {
+                    continue;
+                }
+                for k in j..self.columns {
+                    self.elements[i * self.columns + k] -= factor * self.get(row, k);
+                }
+                for k in 0..other.columns {
+                    other.elements[i * other.columns + k] -= factor * other.get(row, k);
+                }
+            }
+            pivots.push(j);
+        }
+        (pivots, sign)
+    }
+    pub fn determinant(&self) -> Result<f64, EvalError> {
+        let size = self.is_square()?;
+        let mut echelon = self.clone();
+        let (pivots, sign) = echelon.eliminate(&mut Matrix::zeros(size, 0), 0.0);
+        if pivots.len() < size {
+            return Ok(0.0);
+        }
+        Ok((0..size).fold(sign, |product, i| product * echelon.get(i, i)))
+    }
+    /// Number of linearly independent rows, elements at most `tolerance` times the largest one
+    /// being taken as zero.
+    pub fn rank(&self, tolerance: f64) -> usize {
+        let mut echelon = self.clone();
+        let tolerance = tolerance.max(f64::EPSILON * self.rows.max(self.columns) as f64);
+        echelon
+            .eliminate(&mut Matrix::zeros(self.rows, 0), tolerance)
+            .0
+            .len()
+    }
+    /// The matrix `x` such that `self * x = b`, `self` having to be square and invertible up to
+    /// rounding errors.
+    pub fn solve(&self, b: &Matrix) -> Result<Matrix, EvalError> {
+        let size = self.is_square()?;
+        if b.rows != size {
+            return Err(EvalError::DimensionMismatch(format!(
+                "cannot solve a {}x{} system for a {}x{} right-hand side",
+                size, size, b.rows, b.columns
+            )));
+        }
+        let mut echelon = self.clone();
+        let mut x = b.clone();
+        let (pivots, _) = echelon.eliminate(&mut x, f64::EPSILON * size as f64);
+        if pivots.len() < size {
+            return Err(EvalError::SingularMatrix);
+        }
+        for i in (0..size).rev() {
+            for k in 0..x.columns {
+                let mut value = x.get(i, k);
+                for j in (i + 1)..size {
+                    value -= echelon.get(i, j) * x.get(j, k);
+                }
+                x.elements[i * x.columns + k] = value / echelon.get(i, i);
+            }
+        }
+        Ok(x)
+    }
+    pub fn inverse(&self) -> Result<Matrix, EvalError> {
+        self.solve(&Matrix::identity(self.is_square()?))
+    }
+    fn is_symmetric(&self) -> bool {
+        let scale = self.elements.iter().fold(0.0, |max, x| x.abs().max(max));
+        (0..self.rows).all(|i| {
+            (0..i).all(|j| (self.get(i, j) - self.get(j, i)).abs() <= f64::EPSILON * scale)
+        })
+    }
+    /// Every eigenvalue of the matrix, from Jacobi rotations when it's symmetric, and otherwise
+    /// from the shifted QR algorithm applied to its Hessenberg form.
+    pub fn eigenvalues(&self) -> Result<Vec<Complex64>, EvalError> {
+        self.is_square()?;
+        if self.is_symmetric() {
+            let values = self.jacobi_eigenvalues();
+            return Ok(values.into_iter().map(|x| Complex64::new(x, 0.0)).collect());
+        }
+        self.hessenberg().hessenberg_eigenvalues()
+    }
+    /// Cyclic Jacobi method, zeroing every element off the diagonal in turn with a rotation
+    /// until they are all negligible.
+    fn jacobi_eigenvalues(&self) -> Vec<f64> {
+        let size = self.rows;
+        let mut a = self.clone();
+        let norm = a.elements.iter().map(|x| x * x).sum::<f64>().sqrt();
+        for _ in 0..MAX_JACOBI_SWEEPS {
+            let off_diagonal = (0..size)
+                .flat_map(|i| (0..size).filter(move |&j| j != i).map(move |j| (i, j)))
+                .map(|(i, j)| a.get(i, j).powi(2))
+                .sum::<f64>()
+                .sqrt();
+            if off_diagonal <= f64::EPSILON * norm {
+                break;
+            }
+            for p in 0..size {
+                for q in (p + 1)..size {
+                    let apq = a.get(p, q);
+                    if apq == 0.0 {
+                        continue;
+                    }
+                    let theta = (a.get(q, q) - a.get(p, p)) / (2.0 * apq);
+                    let t = if theta.abs() > 1e150 {
+                        0.5 / theta
+                    } else {
+                        theta.signum() / (theta.abs() + (theta * theta + 1.0).sqrt())
+                    };
+                    let c = 1.0 / (t * t + 1.0).sqrt();
+                    let s = t * c;
+                    for k in 0..size {
+                        let (akp, akq) = (a.get(k, p), a.get(k, q));
+                        a.elements[k * size + p] = c * akp - s * akq;
+                        a.elements[k * size + q] = s * akp + c * akq;
+                    }
+                    for k in 0..size {
+                        let (apk, aqk) = (a.get(p, k), a.get(q, k));
+                        a.elements[p * size + k] = c * apk - s * aqk;
+                        a.elements[q * size + k] = s * apk + c * aqk;
+                    }
+                }
+            }
+        }
+        (0..size).map(|i| a.get(i, i)).collect()
+    }
+    /// A matrix with the same eigenvalues and only zeros below its subdiagonal, from
+    /// Householder reflections.
+    fn hessenberg(&self) -> Matrix {
+        let size = self.rows;
+        let mut a = self.clone();
+        for k in 0..size.saturating_sub(2) {
+            let mut v: Vec<f64> = ((k + 1)..size).map(|i| a.get(i, k)).collect();
+            let norm = v.iter().map(|x| x * x).sum::<f64>().sqrt();
+            if norm == 0.0 {
+                continue;
+            }
+            v[0] += if v[0] >= 0.0 { norm } else { -norm };
+            let v_norm = v.iter().map(|x| x * x).sum::<f64>();
+            for j in 0..size {
+                let dot: f64 = v
+                    .iter()
+                    .enumerate()
+                    .map(|(i, vi)| vi * a.get(k + 1 + i, j))
+                    .sum();
+                for (i, vi) in v.iter().enumerate() {
+                    a.elements[(k + 1 + i) * size + j] -= 2.0 * dot * vi / v_norm;
+                }
+            }
+            for i in 0..size {
+                let dot: f64 = v
+                    .iter()
+                    .enumerate()
+                    .map(|(j, vj)| a.get(i, k + 1 + j) * vj)
+                    .sum();
+                for (j, vj) in v.iter().enumerate() {
+                    a.elements[i * size + k + 1 + j] -= 2.0 * dot * vj / v_norm;
+                }
+            }
+            for i in (k + 2)..size {
+                a.elements[i * size + k] = 0.0;
+            }
+        }
+        a
+    }
+    /// Eigenvalues of a Hessenberg matrix from the QR algorithm with Francis double shifts,
+    /// which deflates one real eigenvalue or a pair of complex conjugate ones at a time from
+    /// the bottom of the matrix.
+    fn hessenberg_eigenvalues(mut self) -> Result<Vec<Complex64>, EvalError> {
+        let size = self.rows;
+        // Indices start at 1 like in the usual statement of the algorithm.
+        let index = |i: usize, j: usize| (i - 1) * size + (j - 1);
+        let a = &mut self.elements;
+        let mut norm = 0.0;
+        for i in 1..=size {
+            for j in i.saturating_sub(1).max(1)..=size {
+                norm += a[index(i, j)].abs();
+            }
+        }
+        let mut eigenvalues = vec![Complex64::new(0.0, 0.0); size + 1];
+        let mut nn = size;
+        let mut t = 0.0;
+        while nn >= 1 {
+            let mut iterations = 0;
+            loop {
+                // Look for a negligible subdiagonal element splitting the matrix.
+                let mut l = nn;
+                while l >= 2 {
+                    let mut s = a[index(l - 1, l - 1)].abs() + a[index(l, l)].abs();
+                    if s == 0.0 {
+                        s = norm;
+                    }
+                    if a[index(l, l - 1)].abs() + s == s {
+                        a[index(l, l - 1)] = 0.0;
+                        break;
+                    }
+                    l -= 1;
+                }
+                let mut x = a[index(nn, nn)];
+                if l == nn {
+                    eigenvalues[nn] = Complex64::new(x + t, 0.0);
+                    nn -= 1;
+                    break;
+                }
+                let mut y = a[index(nn - 1, nn - 1)];
+                let mut w = a[index(nn, nn - 1)] * a[index(nn - 1, nn)];
+                if l == nn - 1 {
+                    let p = 0.5 * (y - x);
+                    let q = p * p + w;
+                    let z = q.abs().sqrt();
+                    x += t;
+                    if q >= 0.0 {
+                        let z = p + z.copysign(p);
+                        eigenvalues[nn - 1] = Complex64::new(x + z, 0.0);
+                        eigenvalues[nn] = if z != 0.0 {
+                            Complex64::new(x - w / z, 0.0)
+                        } else {
+                            Complex64::new(x + z, 0.0)
+                        };
+                    } else {
+                        eigenvalues[nn - 1] = Complex64::new(x + p, -z);
+                        eigenvalues[nn] = Complex64::new(x + p, z);
+                    }
+                    nn -= 2;
+                    break;
+                }
+                if iterations == MAX_QR_ITERATIONS {
+                    return Err(EvalError::NoConvergence(
+                        "Finding the eigenvalues".to_string(),
+                    ));
+                }
+                if iterations == 10 || iterations == 20 {
+                    // Exceptional shift, breaking cycles the usual ones can fall into.
+                    t += x;
+                    for i in 1..=nn {
+                        a[index(i, i)] -= x;
+                    }
+                    let s = a[index(nn, nn - 1)].abs() + a[index(nn - 1, nn - 2)].abs();
+                    x = 0.75 * s;
+                    y = x;
+                    w = -0.4375 * s * s;
+                }
+                iterations += 1;
+                // Look for two consecutive small subdiagonal elements to start the sweep from.
+                let mut m = nn - 2;
+                let (mut p, mut q, mut r);
+                loop {
+                    let z = a[index(m, m)];
+                    let rr = x - z;
+                    let s = y - z;
+                    p = (rr * s - w) / a[index(m + 1, m)] + a[index(m, m + 1)];
+                    q = a[index(m + 1, m + 1)] - z - rr - s;
+                    r = a[index(m + 2, m + 1)];
+                    let s = p.abs() + q.abs() + r.abs();
+                    p /= s;
+                    q /= s;
+                    r /= s;
+                    if m == l {
+                        break;
+                    }
+                    let u = a[index(m, m - 1)].abs() * (q.abs() + r.abs());
+                    let v = p.abs()
+                        * (a[index(m - 1, m - 1)].abs() + z.abs() + a[index(m + 1, m + 1)].abs());
+                    if u + v == v {
+                        break;
+                    }
+                    m -= 1;
+                }
+                for i in (m + 2)..=nn {
+                    a[index(i, i - 2)] = 0.0;
+                    if i != m + 2 {
+                        a[index(i, i - 3)] = 0.0;
+                    }
+                }
+                // Double QR step on rows l to nn and columns m to nn.
+                for k in m..nn {
+                    if k != m {
+                        p = a[index(k, k - 1)];
+                        q = a[index(k + 1, k - 1)];
+                        r = if k != nn - 1 {
+                            a[index(k + 2, k - 1)]
+                        } else {
+                            0.0
+                        };
+                        x = p.abs() + q.abs() + r.abs();
+                        if x != 0.0 {
+                            p /= x;
+                            q /= x;
+                            r /= x;
+                        }
+                    }
+                    let s = (p * p + q * q + r * r).sqrt().copysign(p);
+                    if s == 0.0 {
+                        continue;
+                    }
+                    if k == m {
+                        if l != m {
+                            a[index(k, k - 1)] = -a[index(k, k - 1)];
+                        }
+                    } else {
+                        a[index(k, k - 1)] = -s * x;
+                    }
+                    p += s;
+                    x = p / s;
+                    y = q / s;
+                    let z = r / s;
+                    q /= p;
+                    r /= p;
+                    for j in k..=nn {
+                        let mut p = a[index(k, j)] + q * a[index(k + 1, j)];
+                        if k != nn - 1 {
+                            p += r * a[index(k + 2, j)];
+                            a[index(k + 2, j)] -= p * z;
+                        }
+                        a[index(k + 1, j)] -= p * y;
+                        a[index(k, j)] -= p * x;
+                    }
+                    for i in l..=nn.min(k + 3) {
+                        let mut p = x * a[index(i, k)] + y * a[index(i, k + 1)];
+                        if k != nn - 1 {
+                            p += z * a[index(i, k + 2)];
+                            a[index(i, k + 2)] -= p * r;
+                        }
+                        a[index(i, k + 1)] -= p * q;
+                        a[index(i, k)] -= p;
+                    }
+                }
+            }
+        }
+        eigenvalues.remove(0);
+        Ok(eigenvalues)
+    }
+}
+
+impl fmt::Display for Matrix {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "[")?;
+        for i in 0..self.rows {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "[")?;
+            for (j, element) in self.row(i).iter().enumerate() {
+                if j > 0 {
+                    write!(f, ", ")?;
+                }
+                write!(f, "{:?}", element)?;
+            }
+            write!(f, "]")?;
+        }
+        write!(f, "]")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matrix(rows: &[&[f64]]) -> Matrix {
+        Matrix::from_rows(rows.iter().map(|row| row.to_vec()).collect()).unwrap()
+    }
+    fn assert_close(actual: &Matrix, expected: &Matrix) {
+        assert_eq!(
+            (actual.rows, actual.columns),
+            (expected.rows, expected.columns)
+        );
+        for (a, b) in actual.elements.iter().zip(&expected.elements) {
+            assert!((a - b).abs() < 1e-12, "{} != {}", actual, expected);
+        }
+    }
+    #[test]
+    fn test_from_rows() {
+        assert!(Matrix::from_rows(vec![vec![1.0], vec![1.0, 2.0]]).is_none());
+        assert!(Matrix::from_rows(vec![vec![]]).is_none());
+        assert_eq!(
+            matrix(&[&[1.0, 2.0], &[3.0, 4.0]]).transpose().row(0),
+            [1.0, 3.0]
+        );
+    }
+    #[test]
+    fn test_determinant_and_rank() {
+        let a = matrix(&[&[0.0, 2.0, 1.0], &[1.0, 1.0, 1.0], &[2.0, 1.0, 3.0]]);
+        assert!((a.determinant().unwrap() + 3.0).abs() < 1e-12);
+        assert_eq!(a.rank(1e-12), 3);
+        let singular = matrix(&[&[0.1, 0.2], &[0.3, 0.6]]);
+        assert_eq!(singular.rank(1e-12), 1);
+        assert!(singular.inverse().is_err());
+        assert!(matrix(&[&[1.0, 2.0]]).determinant().is_err());
+    }
+    #[test]
+    fn test_solve_and_inverse() {
+        let a = matrix(&[&[2.0, 1.0, -1.0], &[-3.0, -1.0, 2.0], &[-2.0, 1.0, 2.0]]);
+        let x = a.solve(&Matrix::column(vec![8.0, -11.0, -3.0])).unwrap();
+        assert_close(&x, &Matrix::column(vec![2.0, 3.0, -1.0]));
+        assert_close(&a.mul(&a.inverse().unwrap()).unwrap(), &Matrix::identity(3));
+    }
+    fn sorted_eigenvalues(a: &Matrix) -> Vec<Complex64> {
+        let mut eigenvalues = a.eigenvalues().unwrap();
+        eigenvalues.sort_by(|z1, z2| z1.re.total_cmp(&z2.re).then(z1.im.total_cmp(&z2.im)));
+        eigenvalues
+    }
+    fn assert_eigenvalues(a: &Matrix, expected: &[Complex64]) {
+        let eigenvalues = sorted_eigenvalues(a);
+        assert_eq!(eigenvalues.len(), expected.len());
+        for (actual, expected) in eigenvalues.iter().zip(expected) {
+            assert!((actual - expected).norm() < 1e-9, "{:?}", eigenvalues);
+        }
+    }
+    #[test]
+    fn test_symmetric_eigenvalues() {
+        let real = |values: &[f64]| -> Vec<Complex64> {
+            values.iter().map(|&x| Complex64::new(x, 0.0)).collect()
+        };
+        assert_eq!(
+            Matrix::identity(20).eigenvalues().unwrap(),
+            real(&[1.0; 20])
+        );
+        let mut diagonal = Matrix::zeros(12, 12);
+        for i in 0..12 {
+            diagonal.elements[i * 12 + i] = 12.0 - i as f64;
+        }
+        let expected: Vec<f64> = (1..=12).map(f64::from).collect();
+        assert_eigenvalues(&diagonal, &real(&expected));
+        let a = matrix(&[&[2.0, 1.0], &[1.0, 2.0]]);
+        assert_eigenvalues(&a, &real(&[1.0, 3.0]));
+        // The second difference matrix, whose eigenvalues are known in closed form.
+        let size = 15;
+        let mut laplacian = Matrix::zeros(size, size);
+        for i in 0..size {
+            laplacian.elements[i * size + i] = 2.0;
+            if i > 0 {
+                laplacian.elements[i * size + i - 1] = -1.0;
+                laplacian.elements[(i - 1) * size + i] = -1.0;
+            }
+        }
+        let expected: Vec<f64> = (1..=size)
+            .map(|k| {
+                let angle = k as f64 * std::f64::consts::PI / (2.0 * (size + 1) as f64);
+                4.0 * angle.sin().powi(2)
+            })
+            .collect();
+        assert_eigenvalues(&laplacian, &real(&expected));
+    }
+    #[test]
+    fn test_general_eigenvalues() {
+        let rotation = matrix(&[&[0.0, -1.0], &[1.0, 0.0]]);
+        assert_eigenvalues(
+            &rotation,
+            &[Complex64::new(0.0, -1.0), Complex64::new(0.0, 1.0)],
+        );
+        // A triangular matrix has its diagonal as eigenvalues.
+        let size = 10;
+        let mut triangular = Matrix::zeros(size, size);
+        for i in 0..size {
+            for j in i..size {
+                triangular.elements[i * size + j] = if i == j { (i + 1) as f64 } else { 1.0 };
+            }
+        }
+        let expected: Vec<Complex64> = (1..=size).map(|k| Complex64::new(k as f64, 0.0)).collect();
+        assert_eigenvalues(&triangular.transpose(), &expected);
+        // The companion matrix of (x - 1)(x - 2)(x - 3)(x^2 + 1).
+        let companion = matrix(&[
+            &[0.0, 0.0, 0.0, 0.0, 6.0],
+            &[1.0, 0.0, 0.0, 0.0, -11.0],
+            &[0.0, 1.0, 0.0, 0.0, 12.0],
+            &[0.0, 0.0, 1.0, 0.0, -12.0],
+            &[0.0, 0.0, 0.0, 1.0, 6.0],
+        ]);
+        assert_eigenvalues(
+            &companion,
+            &[
+                Complex64::new(0.0, -1.0),
+                Complex64::new(0.0, 1.0),
+                Complex64::new(1.0, 0.0),
+                Complex64::new(2.0, 0.0),
+                Complex64::new(3.0, 0.0),
+            ],
+        );
+        assert!(matrix(&[&[1.0, 2.0]]).eigenvalues().is_err());
+    }
+}
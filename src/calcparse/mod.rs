@@ -1,4 +1,20 @@
 pub mod ast;
+pub mod constants;
+pub mod context;
+pub mod derivative;
+pub mod distribution;
+pub mod error;
+pub mod finance;
+pub mod matrix;
+pub mod number_theory;
+pub mod numeric;
+pub mod operators;
 pub mod parser;
+pub mod polynomial;
+pub mod programmer;
+pub mod special;
+pub mod statistics;
+pub mod suggestion;
 pub mod token;
 pub mod tokenizer;
+pub mod value;
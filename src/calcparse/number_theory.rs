@@ -0,0 +1,381 @@
+use super::error::EvalError;
+
+/// Largest integer below which every integer is exactly represented by a `f64`, 2^53.
+pub const MAX_INTEGER: f64 = 9_007_199_254_740_992.0;
+
+/// Largest `n` whose Fibonacci number is finite.
+const MAX_FIBONACCI: u64 = 1476;
+
+/// Bases for which the Miller–Rabin test is deterministic on every 64-bit integer.
+const WITNESSES: [u64; 12] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37];
+
+/// The error for arguments out of the function's domain.
+fn domain(function: &'static str, arguments: &[f64]) -> EvalError {
+    EvalError::Domain {
+        function,
+        arguments: arguments.to_vec(),
+    }
+}
+
+/// `x` as an integer, rejecting fractions and numbers too large to be told apart from their
+/// neighbours.
+fn integer(x: f64, function: &'static str) -> Result<i64, EvalError> {
+    if x.fract() != 0.0 || x.abs() > MAX_INTEGER {
+        return Err(domain(function, &[x]));
+    }
+    Ok(x as i64)
+}
+
+/// `x` as a non negative integer.
+fn natural(x: f64, function: &'static str) -> Result<u64, EvalError> {
+    match integer(x, function)? {
+        n if n < 0 => Err(domain(function, &[x])),
+        n => Ok(n as u64),
+    }
+}
+
+/// `x` as an integer of at least 2.
+fn modulus(x: f64, function: &'static str) -> Result<u64, EvalError> {
+    match natural(x, function)? {
+        n if n < 2 => Err(domain(function, &[x])),
+        n => Ok(n),
+    }
+}
+
+/// Number of ways to choose `k` elements among `n`, `nCr(n, k)`.
+pub fn binomial(n: f64, k: f64) -> Result<f64, EvalError> {
+    let (n, k) = (natural(n, "nCr")?, natural(k, "nCr")?);
+    if k > n {
+        return Ok(0.0);
+    }
+    let k = k.min(n - k);
+    // Each partial product is itself a binomial coefficient, so the division is exact.
+    let mut exact: Option<u128> = Some(1);
+    let mut approximate = 1.0;
+    for i in 1..=k {
+        let factor = n - k + i;
+        exact = exact
+            .and_then(|c| c.checked_mul(factor as u128))
+            .map(|c| c / i as u128);
+        approximate *= factor as f64 / i as f64;
+        if approximate.is_infinite() {
+            break;
+        }
+    }
+    Ok(exact.map_or(approximate, |c| c as f64))
+}
+
+/// Number of ordered arrangements of `k` elements among `n`, `nPr(n, k)`.
+pub fn permutations(n: f64, k: f64) -> Result<f64, EvalError> {
+    let (n, k) = (natural(n, "nPr")?, natural(k, "nPr")?);
+    if k > n {
+        return Ok(0.0);
+    }
+    let mut result = 1.0;
+    for factor in (n - k + 1)..=n {
+        result *= factor as f64;
+        if result.is_infinite() {
+            break;
+        }
+    }
+    Ok(result)
+}
+
+fn gcd_of(mut a: u64, mut b: u64) -> u64 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+/// Greatest common divisor of integers, always non negative.
+pub fn gcd(args: &[f64]) -> Result<f64, EvalError> {
+    let mut result = 0;
+    for x in args {
+        result = gcd_of(result, integer(*x, "gcd")?.unsigned_abs());
+    }
+    Ok(result as f64)
+}
+
+/// Least common multiple of integers, zero as soon as one of them is.
+pub fn lcm(args: &[f64]) -> Result<f64, EvalError> {
+    let mut result = 1.0;
+    for x in args {
+        let n = integer(*x, "lcm")?.unsigned_abs();
+        if n == 0 {
+            return Ok(0.0);
+        }
+        // Past 2^53 the result can only be approximated.
+        result = if result <= MAX_INTEGER {
+            result / gcd_of(result as u64, n) as f64 * n as f64
+        } else {
+            result * n as f64
+        };
+    }
+    Ok(result)
+}
+
+fn mul_mod(a: u64, b: u64, m: u64) -> u64 {
+    (a as u128 * b as u128 % m as u128) as u64
+}
+
+fn pow_mod(mut base: u64, mut exponent: u64, m: u64) -> u64 {
+    let mut result = 1 % m;
+    base %= m;
+    while exponent > 0 {
+        if exponent & 1 == 1 {
+            result = mul_mod(result, base, m);
+        }
+        base = mul_mod(base, base, m);
+        exponent >>= 1;
+    }
+    result
+}
+
+/// Deterministic Miller–Rabin primality test.
+fn is_prime_integer(n: u64) -> bool {
+    if n < 2 {
+        return false;
+    }
+    for p in WITNESSES {
+        if n.is_multiple_of(p) {
+            return n == p;
+        }
+    }
+    let (mut d, mut s) = (n - 1, 0);
+    while d.is_multiple_of(2) {
+        d /= 2;
+        s += 1;
+    }
+    WITNESSES.iter().all(|&a| {
+        let mut x = pow_mod(a, d, n);
+        if x == 1 || x == n - 1 {
+            return true;
+        }
+        for _ in 1..s {
+            x = mul_mod(x, x, n);
+            if x == n - 1 {
+                return true;
+            }
+        }
+        false
+    })
+}
+
+pub fn is_prime(n: f64) -> Result<bool, EvalError> {
+    let n = integer(n, "isprime")?;
+    Ok(n > 0 && is_prime_integer(n as u64))
+}
+
+/// Smallest prime strictly greater than `n`.
+pub fn next_prime(n: f64) -> Result<f64, EvalError> {
+    let mut candidate = integer(n, "nextprime")?.max(1) as u64 + 1;
+    while !is_prime_integer(candidate) {
+        candidate += 1;
+    }
+    if candidate as f64 > MAX_INTEGER {
+        return Err(EvalError::Overflow {
+            function: "nextprime",
+            arguments: vec![n],
+        });
+    }
+    Ok(candidate as f64)
+}
+
+/// A non trivial divisor of the composite `n`, found with Pollard's rho algorithm.
+fn divisor(n: u64) -> u64 {
+    if n.is_multiple_of(2) {
+        return 2;
+    }
+    for c in 1.. {
+        let f = |x| (mul_mod(x, x, n) + c) % n;
+        let (mut x, mut y, mut d) = (2, 2, 1);
+        while d == 1 {
+            x = f(x);
+            y = f(f(y));
+            d = gcd_of(x.abs_diff(y), n);
+        }
+        if d != n {
+            return d;
+        }
+    }
+    n
+}
+
+/// Prime factors of `n`, repeated according to their multiplicity, in increasing order.
+fn prime_factors(n: u64) -> Vec<u64> {
+    let mut factors = Vec::new();
+    let mut pending = vec![n];
+    while let Some(n) = pending.pop() {
+        if n == 1 {
+            continue;
+        }
+        if is_prime_integer(n) {
+            factors.push(n);
+        } else {
+            let d = divisor(n);
+            pending.extend([d, n / d]);
+        }
+    }
+    factors.sort_unstable();
+    factors
+}
+
+/// Prime factorisation of an integer greater than 1, `-1` coming first for negative ones.
+pub fn factor(n: f64) -> Result<Vec<f64>, EvalError> {
+    let n = integer(n, "factor")?;
+    if n.unsigned_abs() < 2 {
+        return Err(domain("factor", &[n as f64]));
+    }
+    let sign = if n < 0 { Some(-1.0) } else { None };
+    Ok(sign
+        .into_iter()
+        .chain(
+            prime_factors(n.unsigned_abs())
+                .into_iter()
+                .map(|p| p as f64),
+        )
+        .collect())
+}
+
+/// Euler's totient, the count of integers from 1 to `n` coprime with `n`.
+pub fn totient(n: f64) -> Result<f64, EvalError> {
+    let n = natural(n, "totient")?;
+    if n == 0 {
+        return Err(domain("totient", &[0.0]));
+    }
+    let mut factors = prime_factors(n);
+    factors.dedup();
+    Ok(factors.iter().fold(n, |result, p| result / p * (p - 1)) as f64)
+}
+
+/// `base ^ exponent mod m`, the result being between 0 and `m - 1`.
+pub fn mod_pow(base: f64, exponent: f64, m: f64) -> Result<f64, EvalError> {
+    let m = modulus(m, "mod_pow")?;
+    let base = integer(base, "mod_pow")?.rem_euclid(m as i64) as u64;
+    Ok(pow_mod(base, natural(exponent, "mod_pow")?, m) as f64)
+}
+
+/// Inverse of `a` modulo `m`, which exists when they are coprime.
+pub fn mod_inv(a: f64, m: f64) -> Result<f64, EvalError> {
+    let m = modulus(m, "mod_inv")? as i128;
+    let a = integer(a, "mod_inv")? as i128;
+    // Extended Euclidean algorithm, keeping only the coefficients of `a`.
+    let (mut r0, mut r1) = (a.rem_euclid(m), m);
+    let (mut s0, mut s1) = (1, 0);
+    while r1 != 0 {
+        let q = r0 / r1;
+        (r0, r1) = (r1, r0 - q * r1);
+        (s0, s1) = (s1, s0 - q * s1);
+    }
+    if r0 != 1 {
+        return Err(domain("mod_inv", &[a as f64, m as f64]));
+    }
+    Ok(s0.rem_euclid(m) as f64)
+}
+
+/// The `n`-th Fibonacci number, `fib(0) = 0` and `fib(1) = 1`.
+pub fn fibonacci(n: f64) -> Result<f64, EvalError> {
+    let n = natural(n, "fib")?;
+    if n > MAX_FIBONACCI {
+        return Ok(f64::INFINITY);
+    }
+    let (mut a, mut b) = (0.0, 1.0);
+    for _ in 0..n {
+        (a, b) = (b, a + b);
+    }
+    Ok(a)
+}
+
+/// `n!!`, the product of the integers from `n` down to 1 or 2 with a step of 2, `(-1)!!` being 1.
+pub fn double_factorial(n: f64) -> Result<f64, EvalError> {
+    let n = integer(n, "double factorial")?;
+    if n < -1 {
+        return Err(domain("double factorial", &[n as f64]));
+    }
+    let mut result: f64 = 1.0;
+    let mut factor = n;
+    while factor > 1 && result.is_finite() {
+        result *= factor as f64;
+        factor -= 2;
+    }
+    Ok(result)
+}
+
+/// Number of permutations of `n` elements leaving none of them in place, also written `!n`.
+pub fn subfactorial(n: f64) -> Result<f64, EvalError> {
+    let n = natural(n, "subfactorial")?;
+    // !n = n !(n - 1) + (-1)^n
+    let mut result: f64 = 1.0;
+    let mut i = 1;
+    while i <= n && result.is_finite() {
+        result = i as f64 * result + if i % 2 == 0 { 1.0 } else { -1.0 };
+        i += 1;
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_combinatorics() {
+        assert_eq!(binomial(5.0, 2.0).unwrap(), 10.0);
+        assert_eq!(binomial(2.0, 5.0).unwrap(), 0.0);
+        assert_eq!(binomial(60.0, 30.0).unwrap(), 118_264_581_564_861_424.0);
+        assert!(binomial(5000.0, 2500.0).unwrap().is_infinite());
+        assert!(binomial(5.5, 2.0).is_err());
+        assert_eq!(permutations(5.0, 2.0).unwrap(), 20.0);
+        assert_eq!(permutations(5.0, 0.0).unwrap(), 1.0);
+        assert_eq!(double_factorial(7.0).unwrap(), 105.0);
+        assert_eq!(double_factorial(8.0).unwrap(), 384.0);
+        assert_eq!(double_factorial(-1.0).unwrap(), 1.0);
+        assert!(double_factorial(-3.0).is_err());
+        assert_eq!(subfactorial(0.0).unwrap(), 1.0);
+        assert_eq!(subfactorial(1.0).unwrap(), 0.0);
+        assert_eq!(subfactorial(5.0).unwrap(), 44.0);
+        assert_eq!(fibonacci(10.0).unwrap(), 55.0);
+        assert_eq!(fibonacci(78.0).unwrap(), 8_944_394_323_791_464.0);
+        assert!(fibonacci(2000.0).unwrap().is_infinite());
+    }
+    #[test]
+    fn test_divisibility() {
+        assert_eq!(gcd(&[12.0, -18.0, 27.0]).unwrap(), 3.0);
+        assert_eq!(gcd(&[0.0, 5.0]).unwrap(), 5.0);
+        assert_eq!(lcm(&[4.0, 6.0, 10.0]).unwrap(), 60.0);
+        assert_eq!(lcm(&[4.0, 0.0]).unwrap(), 0.0);
+        assert!(gcd(&[1.5, 3.0]).is_err());
+        assert!(lcm(&[1e300]).is_err());
+        assert_eq!(mod_pow(4.0, 13.0, 497.0).unwrap(), 445.0);
+        assert_eq!(mod_pow(-2.0, 3.0, 5.0).unwrap(), 2.0);
+        assert_eq!(mod_inv(3.0, 11.0).unwrap(), 4.0);
+        assert_eq!(mod_inv(-3.0, 11.0).unwrap(), 7.0);
+        assert!(mod_inv(6.0, 9.0).is_err());
+        assert!(mod_pow(2.0, -1.0, 5.0).is_err());
+    }
+    #[test]
+    fn test_primes() {
+        let primes: Vec<f64> = (0..30)
+            .map(f64::from)
+            .filter(|n| is_prime(*n).unwrap())
+            .collect();
+        assert_eq!(
+            primes,
+            [2.0, 3.0, 5.0, 7.0, 11.0, 13.0, 17.0, 19.0, 23.0, 29.0]
+        );
+        assert!(is_prime(9_007_199_254_740_881.0).unwrap());
+        assert!(!is_prime(3_215_031_751.0).unwrap());
+        assert_eq!(next_prime(13.0).unwrap(), 17.0);
+        assert_eq!(next_prime(-5.0).unwrap(), 2.0);
+        assert_eq!(factor(360.0).unwrap(), [2.0, 2.0, 2.0, 3.0, 3.0, 5.0]);
+        assert_eq!(factor(-14.0).unwrap(), [-1.0, 2.0, 7.0]);
+        assert_eq!(
+            factor(9_007_199_254_740_991.0).unwrap(),
+            [6361.0, 69_431.0, 20_394_401.0]
+        );
+        assert!(factor(1.0).is_err());
+        assert_eq!(totient(36.0).unwrap(), 12.0);
+        assert_eq!(totient(1.0).unwrap(), 1.0);
+    }
+}
@@ -0,0 +1,489 @@
+use super::error::EvalError;
+
+/// Most intervals the adaptive quadrature may split the integration range into.
+pub const MAX_SUBDIVISIONS: usize = 2000;
+/// Most steps the root finders may take before giving up.
+pub const MAX_ITERATIONS: usize = 200;
+
+/// Kronrod nodes on [-1, 1], the odd ones being the 7 point Gauss nodes. Only the non negative
+/// half is listed, the rule being symmetric.
+const KRONROD_NODES: [f64; 8] = [
+    0.991_455_371_120_812_6,
+    0.949_107_912_342_758_5,
+    0.864_864_423_359_769_1,
+    0.741_531_185_599_394_4,
+    0.586_087_235_467_691_1,
+    0.405_845_151_377_397_2,
+    0.207_784_955_007_898_5,
+    0.0,
+];
+const KRONROD_WEIGHTS: [f64; 8] = [
+    0.022_935_322_010_529_22,
+    0.063_092_092_629_978_55,
+    0.104_790_010_322_250_18,
+    0.140_653_259_715_525_9,
+    0.169_004_726_639_267_9,
+    0.190_350_578_064_785_4,
+    0.204_432_940_075_298_9,
+    0.209_482_141_084_727_83,
+];
+const GAUSS_WEIGHTS: [f64; 4] = [
+    0.129_484_966_168_869_7,
+    0.279_705_391_489_276_7,
+    0.381_830_050_505_118_9,
+    0.417_959_183_673_469_4,
+];
+
+/// Result of a numerical method along with an estimate of its absolute error.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Estimate {
+    pub value: f64,
+    pub error: f64,
+}
+
+struct Segment {
+    start: f64,
+    end: f64,
+    value: f64,
+    error: f64,
+    /// Integral of the absolute value, used to tell when the error is down to rounding.
+    magnitude: f64,
+}
+
+pub type Function<'a> = dyn FnMut(f64) -> Result<f64, EvalError> + 'a;
+
+/// Integrate `f` from `a` to `b` with an adaptive 7-15 points Gauss–Kronrod rule, aiming at a
+/// relative error of `precision`. Infinite bounds are mapped to a finite range beforehand.
+pub fn integrate(f: &mut Function, a: f64, b: f64, precision: f64) -> Result<Estimate, EvalError> {
+    if a.is_nan() || b.is_nan() {
+        return Err(EvalError::Domain {
+            function: "integrate",
+            arguments: vec![a, b],
+        });
+    }
+    if a == b {
+        return Ok(Estimate {
+            value: 0.0,
+            error: 0.0,
+        });
+    }
+    if a > b {
+        let integral = integrate(f, b, a, precision)?;
+        return Ok(Estimate {
+            value: -integral.value,
+            ..integral
+        });
+    }
+    let mut f = |x: f64| -> Result<f64, EvalError> {
+        let y = f(x)?;
+        if y.is_finite() {
+            Ok(y)
+        } else {
+            Err(EvalError::Domain {
+                function: "the integrand",
+                arguments: vec![x],
+            })
+        }
+    };
+    match (a.is_finite(), b.is_finite()) {
+        (true, true) => adaptive(&mut f, a, b, precision),
+        // x = a + t / (1 - t), t going from 0 to 1.
+        (true, false) => adaptive(
+            &mut |t| Ok(f(a + t / (1.0 - t))? / ((1.0 - t) * (1.0 - t))),
+            0.0,
+            1.0,
+            precision,
+        ),
+        // x = b - (1 - t) / t, t going from 0 to 1.
+        (false, true) => adaptive(
+            &mut |t| Ok(f(b - (1.0 - t) / t)? / (t * t)),
+            0.0,
+            1.0,
+            precision,
+        ),
+        // x = t / (1 - t^2), t going from -1 to 1.
+        (false, false) => adaptive(
+            &mut |t| {
+                let denominator = 1.0 - t * t;
+                Ok(f(t / denominator)? * (1.0 + t * t) / (denominator * denominator))
+            },
+            -1.0,
+            1.0,
+            precision,
+        ),
+    }
+}
+
+/// Keep splitting the segment with the largest error until the total error is small enough.
+fn adaptive(f: &mut Function, a: f64, b: f64, precision: f64) -> Result<Estimate, EvalError> {
+    let mut segments = vec![gauss_kronrod(f, a, b)?];
+    loop {
+        let value: f64 = segments.iter().map(|segment| segment.value).sum();
+        let error: f64 = segments.iter().map(|segment| segment.error).sum();
+        let magnitude: f64 = segments.iter().map(|segment| segment.magnitude).sum();
+        if error <= precision * value.abs() || error <= 50.0 * f64::EPSILON * magnitude {
+            return Ok(Estimate { value, error });
+        }
+        let worst = segments
+            .iter()
+            .enumerate()
+            .max_by(|(_, s1), (_, s2)| s1.error.total_cmp(&s2.error))
+            .map(|(index, _)| index)
+            .unwrap_or(0);
+        let Segment { start, end, .. } = segments.swap_remove(worst);
+        let middle = 0.5 * (start + end);
+        if segments.len() + 2 > MAX_SUBDIVISIONS || middle <= start || middle >= end {
+            return Err(EvalError::NoConvergence(format!(
+                "The integral, estimated at {:?} with an error of {:e},",
+                value, error
+            )));
+        }
+        segments.push(gauss_kronrod(f, start, middle)?);
+        segments.push(gauss_kronrod(f, middle, end)?);
+    }
+}
+
+fn gauss_kronrod(f: &mut Function, start: f64, end: f64) -> Result<Segment, EvalError> {
+    let center = 0.5 * (start + end);
+    let half_length = 0.5 * (end - start);
+    let middle = f(center)?;
+    let mut kronrod = middle * KRONROD_WEIGHTS[7];
+    let mut gauss = middle * GAUSS_WEIGHTS[3];
+    let mut magnitude = middle.abs() * KRONROD_WEIGHTS[7];
+    for (index, node) in KRONROD_NODES[..7].iter().enumerate() {
+        let left = f(center - half_length * node)?;
+        let right = f(center + half_length * node)?;
+        kronrod += KRONROD_WEIGHTS[index] * (left + right);
+        magnitude += KRONROD_WEIGHTS[index] * (left.abs() + right.abs());
+        if index % 2 == 1 {
+            gauss += GAUSS_WEIGHTS[index / 2] * (left + right);
+        }
+    }
+    Ok(Segment {
+        start,
+        end,
+        value: kronrod * half_length,
+        error: ((kronrod - gauss) * half_length).abs(),
+        magnitude: magnitude * half_length.abs(),
+    })
+}
+
+/// Evaluate `f`, rejecting values that can't be compared to zero.
+fn sample(f: &mut Function, x: f64) -> Result<f64, EvalError> {
+    let y = f(x)?;
+    if y.is_nan() {
+        Err(EvalError::Domain {
+            function: "the function",
+            arguments: vec![x],
+        })
+    } else {
+        Ok(y)
+    }
+}
+
+/// Find a root of `f` between `a` and `b` with Brent's method, `f(a)` and `f(b)` having
+/// opposite signs.
+pub fn brent(f: &mut Function, a: f64, b: f64, precision: f64) -> Result<f64, EvalError> {
+    let (mut a, mut b) = (a, b);
+    let (mut fa, mut fb) = (sample(f, a)?, sample(f, b)?);
+    if fa == 0.0 {
+        return Ok(a);
+    }
+    if fb == 0.0 {
+        return Ok(b);
+    }
+    if (fa > 0.0) == (fb > 0.0) {
+        return Err(EvalError::InvalidArgument(format!(
+            "No sign change in bracket [{:?}, {:?}]",
+            a, b
+        )));
+    }
+    let (mut c, mut fc) = (a, fa);
+    let mut step = b - a;
+    let mut previous_step = step;
+    for _ in 0..MAX_ITERATIONS {
+        if (fb > 0.0) == (fc > 0.0) {
+            c = a;
+            fc = fa;
+            step = b - a;
+            previous_step = step;
+        }
+        // Keep b as the best estimate and c on the other side of the root.
+        if fc.abs() < fb.abs() {
+            a = b;
+            b = c;
+            c = a;
+            fa = fb;
+            fb = fc;
+            fc = fa;
+        }
+        let tolerance = 2.0 * f64::EPSILON * b.abs() + 0.5 * precision * b.abs().max(1.0);
+        let middle = 0.5 * (c - b);
+        if middle.abs() <= tolerance || fb == 0.0 {
+            return Ok(b);
+        }
+        if previous_step.abs() >= tolerance && fa.abs() > fb.abs() {
+            // Secant step when only two points are known, inverse quadratic interpolation otherwise.
+            let s = fb / fa;
+            let (mut p, mut q) = if a == c {
+                (2.0 * middle * s, 1.0 - s)
+            } else {
+                let q = fa / fc;
+                let r = fb / fc;
+                (
+                    s * (2.0 * middle * q * (q - r) - (b - a) * (r - 1.0)),
+                    (q - 1.0) * (r - 1.0) * (s - 1.0),
+                )
+            };
+            if p > 0.0 {
+                q = -q;
+            } else {
+                p = -p;
+            }
+            if 2.0 * p < (3.0 * middle * q - (tolerance * q).abs()).min((previous_step * q).abs()) {
+                previous_step = step;
+                step = p / q;
+            } else {
+                step = middle;
+                previous_step = middle;
+            }
+        } else {
+            step = middle;
+            previous_step = middle;
+        }
+        a = b;
+        fa = fb;
+        b += if step.abs() > tolerance {
+            step
+        } else {
+            tolerance.copysign(middle)
+        };
+        fb = sample(f, b)?;
+    }
+    Err(EvalError::NoConvergence(format!(
+        "Root finding, last estimate {:?},",
+        b
+    )))
+}
+
+/// Newton's method from `guess`, `f` giving both the function and its derivative.
+/// `None` when it doesn't converge.
+pub fn newton(
+    f: &mut dyn FnMut(f64) -> Result<(f64, f64), EvalError>,
+    guess: f64,
+    precision: f64,
+) -> Result<Option<f64>, EvalError> {
+    let mut x = guess;
+    for _ in 0..MAX_ITERATIONS {
+        let (y, slope) = f(x)?;
+        let step = y / slope;
+        if !step.is_finite() {
+            return Ok(None);
+        }
+        x -= step;
+        if step.abs() <= precision * x.abs().max(1.0) {
+            return Ok(Some(x));
+        }
+    }
+    Ok(None)
+}
+
+/// Look for an interval around `guess` over which `f` changes sign, widening it geometrically.
+pub fn bracket(f: &mut Function, guess: f64) -> Result<(f64, f64), EvalError> {
+    let mut width = 0.01 * guess.abs().max(1.0);
+    let mut previous = (guess, f(guess)?);
+    let mut previous_left = previous;
+    for _ in 0..MAX_ITERATIONS {
+        for (x, last) in [
+            (guess + width, &mut previous),
+            (guess - width, &mut previous_left),
+        ] {
+            let y = f(x)?;
+            if y == 0.0 || (!y.is_nan() && !last.1.is_nan() && (y > 0.0) != (last.1 > 0.0)) {
+                return Ok(if x < last.0 { (x, last.0) } else { (last.0, x) });
+            }
+            *last = (x, y);
+        }
+        width *= 1.6;
+        if !width.is_finite() {
+            break;
+        }
+    }
+    Err(EvalError::NoConvergence(format!(
+        "The search for a sign change around {:?}",
+        guess
+    )))
+}
+
+/// Derivative of `f` at `x`, extrapolating central differences of shrinking steps (Ridders'
+/// method) and stopping once the extrapolation stops improving.
+pub fn derivative(f: &mut Function, x: f64) -> Result<Estimate, EvalError> {
+    const SHRINK: f64 = 1.4;
+    const ROWS: usize = 10;
+    let mut step = 0.1 * x.abs().max(1.0);
+    let mut central = |step: f64| -> Result<f64, EvalError> {
+        Ok((sample(f, x + step)? - sample(f, x - step)?) / (2.0 * step))
+    };
+    let mut table = vec![vec![central(step)?]];
+    let mut best = Estimate {
+        value: table[0][0],
+        error: f64::INFINITY,
+    };
+    for i in 1..ROWS {
+        step /= SHRINK;
+        let mut row = vec![central(step)?];
+        let mut factor = SHRINK * SHRINK;
+        for j in 1..=i {
+            // Eliminate the next even power of the step from the error.
+            let value = (row[j - 1] * factor - table[i - 1][j - 1]) / (factor - 1.0);
+            factor *= SHRINK * SHRINK;
+            let error = (value - row[j - 1])
+                .abs()
+                .max((value - table[i - 1][j - 1]).abs());
+            if error <= best.error {
+                best = Estimate { value, error };
+            }
+            row.push(value);
+        }
+        let diverging = (row[i] - table[i - 1][i - 1]).abs() >= 2.0 * best.error;
+        table.push(row);
+        if diverging {
+            break;
+        }
+    }
+    Ok(best)
+}
+
+/// Limit of `f` as `x` goes to `a`, from above when `direction` is positive and from below
+/// otherwise. Samples at halving distances are extrapolated to a zero distance, assuming `f`
+/// behaves like a power series there. Infinite limits are recognized when the samples keep
+/// growing at least as fast as `1 / x`.
+pub fn limit(
+    f: &mut Function,
+    a: f64,
+    direction: f64,
+    precision: f64,
+) -> Result<Estimate, EvalError> {
+    const ROWS: usize = 24;
+    if a.is_infinite() {
+        // x = ±1 / t, t going to zero from above.
+        let sign = a.signum();
+        return limit(&mut |t| f(sign / t), 0.0, 1.0, precision);
+    }
+    let mut distance = direction.signum() * 0.125 * a.abs().max(1.0);
+    let mut table: Vec<Vec<f64>> = Vec::new();
+    let mut best = Estimate {
+        value: f64::NAN,
+        error: f64::INFINITY,
+    };
+    for i in 0..ROWS {
+        let mut row = vec![sample(f, a + distance)?];
+        distance /= 2.0;
+        let mut factor = 2.0;
+        for j in 1..=i {
+            row.push(row[j - 1] + (row[j - 1] - table[i - 1][j - 1]) / (factor - 1.0));
+            factor *= 2.0;
+        }
+        if i > 0 {
+            let error = (row[i] - table[i - 1][i - 1]).abs();
+            if error <= best.error {
+                best = Estimate {
+                    value: row[i],
+                    error,
+                };
+            }
+        }
+        table.push(row);
+        if best.error <= precision * best.value.abs().max(1.0) {
+            return Ok(best);
+        }
+    }
+    let samples: Vec<f64> = table.iter().map(|row| row[0]).collect();
+    let growing =
+        samples.windows(2).rev().take(4).all(|pair| {
+            pair[1].signum() == pair[0].signum() && pair[1].abs() >= 1.9 * pair[0].abs()
+        });
+    if growing {
+        return Ok(Estimate {
+            value: f64::INFINITY.copysign(samples[ROWS - 1]),
+            error: 0.0,
+        });
+    }
+    if best.error <= precision.sqrt() * best.value.abs().max(1.0) {
+        return Ok(best);
+    }
+    Err(EvalError::NoConvergence(format!("The limit at {:?}", a)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn integral(f: fn(f64) -> f64, a: f64, b: f64) -> Estimate {
+        integrate(&mut |x| Ok(f(x)), a, b, 1e-10).unwrap()
+    }
+
+    #[test]
+    fn test_polynomial() {
+        let result = integral(|x| x * x, 0.0, 3.0);
+        assert!((result.value - 9.0).abs() < 1e-12);
+        assert!(result.error < 1e-9);
+        assert!((integral(|x| x * x, 3.0, 0.0).value + 9.0).abs() < 1e-12);
+    }
+    #[test]
+    fn test_improper() {
+        let gaussian = integral(|x| (-x * x).exp(), f64::NEG_INFINITY, f64::INFINITY);
+        assert!((gaussian.value - std::f64::consts::PI.sqrt()).abs() < 1e-9);
+        let tail = integral(|x| 1.0 / (x * x), 1.0, f64::INFINITY);
+        assert!((tail.value - 1.0).abs() < 1e-9);
+        let left = integral(|x| x.exp(), f64::NEG_INFINITY, 0.0);
+        assert!((left.value - 1.0).abs() < 1e-9);
+    }
+    #[test]
+    fn test_singular_endpoint() {
+        let result = integral(|x| 1.0 / x.sqrt(), 0.0, 1.0);
+        assert!((result.value - 2.0).abs() < 1e-8);
+    }
+    #[test]
+    fn test_brent() {
+        let root = brent(&mut |x| Ok(x * x - 2.0), 0.0, 2.0, 1e-12).unwrap();
+        assert!((root - 2f64.sqrt()).abs() < 1e-12);
+        let root = brent(&mut |x| Ok(x.cos() - x), 0.0, 1.0, 1e-12).unwrap();
+        assert!((root - 0.739_085_133_215_160_6).abs() < 1e-12);
+        assert!(brent(&mut |x| Ok(x * x + 1.0), -1.0, 1.0, 1e-12).is_err());
+    }
+    #[test]
+    fn test_newton_and_bracket() {
+        let root = newton(&mut |x| Ok((x * x - 2.0, 2.0 * x)), 1.0, 1e-12);
+        assert!((root.unwrap().unwrap() - 2f64.sqrt()).abs() < 1e-12);
+        let (a, b) = bracket(&mut |x| Ok(x - 1000.0), 0.0).unwrap();
+        assert!(a <= 1000.0 && 1000.0 <= b);
+    }
+    #[test]
+    fn test_derivative() {
+        let result = derivative(&mut |x| Ok(x.sin()), 1.0).unwrap();
+        assert!((result.value - 1f64.cos()).abs() < 1e-12);
+        let result = derivative(&mut |x| Ok(x.exp()), 10.0).unwrap();
+        assert!((result.value - 10f64.exp()).abs() < 1e-12 * 10f64.exp());
+        assert!(derivative(&mut |x| Ok(x.sqrt()), 0.0).is_err());
+    }
+    #[test]
+    fn test_limit() {
+        let result = limit(&mut |x| Ok(x.sin() / x), 0.0, 1.0, 1e-10).unwrap();
+        assert!((result.value - 1.0).abs() < 1e-10);
+        let result = limit(
+            &mut |x| Ok((1.0 + 1.0 / x).powf(x)),
+            f64::INFINITY,
+            1.0,
+            1e-10,
+        );
+        assert!((result.unwrap().value - std::f64::consts::E).abs() < 1e-6);
+        let result = limit(&mut |x| Ok(1.0 / x), 0.0, -1.0, 1e-10).unwrap();
+        assert_eq!(result.value, f64::NEG_INFINITY);
+        assert!(limit(&mut |x| Ok((1.0 / x).sin()), 0.0, 1.0, 1e-10).is_err());
+    }
+    #[test]
+    fn test_divergent() {
+        assert!(integrate(&mut |x| Ok(1.0 / x), 0.0, 1.0, 1e-10).is_err());
+    }
+}
@@ -0,0 +1,281 @@
+use super::token::{OperPrec, Token};
+
+/// Where an operator stands relative to its operands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Fixity {
+    /// Before its operand, as in `-x`.
+    Prefix,
+    /// Between its operands, as in `x + y`.
+    Infix,
+    /// After its operand, as in `x!`.
+    Postfix,
+}
+
+/// How a chain of operators of the same precedence is grouped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Associativity {
+    /// `a - b - c` is `(a - b) - c`.
+    Left,
+    /// `a ^ b ^ c` is `a ^ (b ^ c)`.
+    Right,
+}
+
+/// How tightly an operator binds. An operator takes as operand everything binding tighter than
+/// it, so `-2^2` is `-(2^2)` since `^` binds tighter than the unary minus, and itself again when
+/// it's right associative.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Operator {
+    pub symbol: &'static str,
+    pub token: Token,
+    pub fixity: Fixity,
+    pub precedence: OperPrec,
+    /// Only meaningful for infix operators, prefix and postfix ones are listed as right and left
+    /// associative since that's how they chain, as in `--x` and `x!!`.
+    pub associativity: Associativity,
+}
+
+const fn operator(
+    symbol: &'static str,
+    token: Token,
+    fixity: Fixity,
+    precedence: OperPrec,
+    associativity: Associativity,
+) -> Operator {
+    Operator {
+        symbol,
+        token,
+        fixity,
+        precedence,
+        associativity,
+    }
+}
+
+const fn infix(symbol: &'static str, token: Token, precedence: OperPrec) -> Operator {
+    operator(
+        symbol,
+        token,
+        Fixity::Infix,
+        precedence,
+        Associativity::Left,
+    )
+}
+
+const fn prefix(symbol: &'static str, token: Token, precedence: OperPrec) -> Operator {
+    operator(
+        symbol,
+        token,
+        Fixity::Prefix,
+        precedence,
+        Associativity::Right,
+    )
+}
+
+const fn postfix(symbol: &'static str, token: Token, precedence: OperPrec) -> Operator {
+    operator(
+        symbol,
+        token,
+        Fixity::Postfix,
+        precedence,
+        Associativity::Left,
+    )
+}
+
+/// The operators the parser knows, from the loosest to the tightest. The branches of `c ? x : y`
+/// are whole expressions, only the condition is bound by the precedence of `?`. A `|` closing an
+/// absolute value ends the expression rather than being a bitwise or.
+pub const OPERATORS: &[Operator] = &[
+    operator(
+        "? :",
+        Token::Question,
+        Fixity::Infix,
+        OperPrec::Conditional,
+        Associativity::Right,
+    ),
+    infix("or", Token::Or, OperPrec::Or),
+    infix("and", Token::And, OperPrec::And),
+    prefix("not", Token::Not, OperPrec::Not),
+    infix("<", Token::Less, OperPrec::Comparison),
+    infix("<=", Token::LessEqual, OperPrec::Comparison),
+    infix(">", Token::Greater, OperPrec::Comparison),
+    infix(">=", Token::GreaterEqual, OperPrec::Comparison),
+    infix("==", Token::Equal, OperPrec::Comparison),
+    infix("!=", Token::NotEqual, OperPrec::Comparison),
+    infix("..", Token::Range, OperPrec::Range),
+    infix("|", Token::Bar, OperPrec::BitOr),
+    infix("xor", Token::Xor, OperPrec::BitXor),
+    infix("&", Token::Ampersand, OperPrec::BitAnd),
+    infix("<<", Token::ShiftLeft, OperPrec::Shift),
+    infix(">>", Token::ShiftRight, OperPrec::Shift),
+    infix("+", Token::Add, OperPrec::AddSub),
+    infix("-", Token::Subtract, OperPrec::AddSub),
+    infix("*", Token::Multiply, OperPrec::MulDiv),
+    infix("/", Token::Divide, OperPrec::MulDiv),
+    infix("\\", Token::Backslash, OperPrec::MulDiv),
+    infix("//", Token::IntegerDivide, OperPrec::MulDiv),
+    infix("%", Token::Modulo, OperPrec::MulDiv),
+    postfix("°", Token::DegToRad, OperPrec::MulDiv),
+    postfix("rad", Token::RadToDeg, OperPrec::MulDiv),
+    prefix("-", Token::Subtract, OperPrec::Negative),
+    prefix("+", Token::Add, OperPrec::Negative),
+    prefix("~", Token::Tilde, OperPrec::Negative),
+    operator(
+        "^",
+        Token::Caret,
+        Fixity::Infix,
+        OperPrec::Power,
+        Associativity::Right,
+    ),
+    postfix("²", Token::Pow2, OperPrec::Power),
+    postfix("³", Token::Pow3, OperPrec::Power),
+    postfix("!", Token::ExclamationMark, OperPrec::Functional),
+    postfix("!!", Token::DoubleExclamationMark, OperPrec::Functional),
+    postfix("[ ]", Token::LeftBracket, OperPrec::Functional),
+];
+
+/// The precedence and associativity the parser reads operators with, the standard `OPERATORS`
+/// unless an embedder changed them with `with`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OperatorTable {
+    operators: Vec<Operator>,
+}
+
+impl Default for OperatorTable {
+    fn default() -> Self {
+        OperatorTable {
+            operators: OPERATORS.to_vec(),
+        }
+    }
+}
+
+impl OperatorTable {
+    pub fn operators(&self) -> &[Operator] {
+        &self.operators
+    }
+    pub fn find(&self, token: &Token, fixity: Fixity) -> Option<&Operator> {
+        self.operators
+            .iter()
+            .find(|operator| operator.token == *token && operator.fixity == fixity)
+    }
+    /// Change how the operator of `token` binds. Tokens that aren't operators with this fixity
+    /// are left alone, since the parser wouldn't know what to make of them.
+    pub fn with(
+        mut self,
+        token: Token,
+        fixity: Fixity,
+        precedence: OperPrec,
+        associativity: Associativity,
+    ) -> Self {
+        if let Some(operator) = self
+            .operators
+            .iter_mut()
+            .find(|operator| operator.token == token && operator.fixity == fixity)
+        {
+            operator.precedence = precedence;
+            operator.associativity = associativity;
+        }
+        self
+    }
+    /// How tightly `token` binds to the expression before it. A function right after an
+    /// operand binds too, so that it's reported as a missing operator rather than ignored.
+    pub fn precedence(&self, token: &Token) -> OperPrec {
+        if let Token::ExplicitFunction(_) = token {
+            return OperPrec::Functional;
+        }
+        self.find(token, Fixity::Infix)
+            .or_else(|| self.find(token, Fixity::Postfix))
+            .map_or(OperPrec::DefaultZero, |operator| operator.precedence)
+    }
+    /// What the operand following `token` is parsed with: operators binding strictly tighter
+    /// than this level become part of it. A right-associative operator goes one level down so
+    /// that it takes itself in.
+    pub fn operand_precedence(&self, token: &Token, fixity: Fixity) -> OperPrec {
+        match self.find(token, fixity) {
+            Some(operator) if operator.associativity == Associativity::Right => {
+                operator.precedence.looser()
+            }
+            Some(operator) => operator.precedence,
+            None => OperPrec::DefaultZero,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_operators_are_unique() {
+        for (i, operator) in OPERATORS.iter().enumerate() {
+            let table = OperatorTable::default();
+            assert_eq!(
+                table.find(&operator.token, operator.fixity),
+                Some(&OPERATORS[i])
+            );
+        }
+    }
+    #[test]
+    fn test_operators_are_sorted() {
+        assert!(OPERATORS
+            .windows(2)
+            .all(|pair| pair[0].precedence <= pair[1].precedence));
+    }
+    #[test]
+    fn test_levels_follow_the_enum() {
+        use OperPrec::*;
+        for (i, level) in OperPrec::LEVELS.iter().enumerate() {
+            assert_eq!(*level as usize, i);
+        }
+        // A level added to the enum stops this match from compiling, as a reminder to add it
+        // to `LEVELS` as well.
+        let is_tightest = |level| match level {
+            Functional => true,
+            DefaultZero | Conditional | Or | And | Not | Comparison | Range | BitOr | BitXor
+            | BitAnd | Shift | AddSub | MulDiv | Negative | Power => false,
+        };
+        assert!(is_tightest(OperPrec::LEVELS[OperPrec::LEVELS.len() - 1]));
+        assert_eq!(DefaultZero.looser(), DefaultZero);
+        assert_eq!(Power.looser(), Negative);
+    }
+    #[test]
+    fn test_operand_precedence() {
+        let table = OperatorTable::default();
+        assert_eq!(
+            table.operand_precedence(&Token::Subtract, Fixity::Infix),
+            OperPrec::AddSub
+        );
+        assert_eq!(
+            table.operand_precedence(&Token::Caret, Fixity::Infix),
+            OperPrec::Negative
+        );
+        assert_eq!(
+            table.operand_precedence(&Token::Subtract, Fixity::Prefix),
+            OperPrec::MulDiv
+        );
+        assert!(table.precedence(&Token::Caret) > table.precedence(&Token::Multiply));
+        assert_eq!(table.precedence(&Token::RightParen), OperPrec::DefaultZero);
+        let table = table.with(
+            Token::Caret,
+            Fixity::Infix,
+            OperPrec::Power,
+            Associativity::Left,
+        );
+        assert_eq!(
+            table.operand_precedence(&Token::Caret, Fixity::Infix),
+            OperPrec::Power
+        );
+        assert_eq!(
+            table.with(
+                Token::Comma,
+                Fixity::Infix,
+                OperPrec::Power,
+                Associativity::Left
+            ),
+            OperatorTable::default().with(
+                Token::Caret,
+                Fixity::Infix,
+                OperPrec::Power,
+                Associativity::Left
+            )
+        );
+    }
+}
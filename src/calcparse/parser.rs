@@ -1,74 +1,314 @@
 use std::fmt;
 
 use super::ast::Node;
+use super::operators::{Fixity, OperatorTable};
+use super::suggestion::{self, Suggestion};
 use super::token::{NativeFunction, OperPrec, Token};
 use super::tokenizer::Tokenizer;
+use super::value::Value;
 
 pub struct Parser<'a> {
     tokenizer: Tokenizer<'a>,
     current_token: Token,
     previous_token: Option<Token>,
-    old_answer: f64,
+    old_answer: Value,
+    closing_tokens: Vec<Token>,
+    depth: usize,
+    max_depth: usize,
+    operators: OperatorTable,
+    /// Whether errors are recorded in `errors` rather than returned, see `parse_with_diagnostics`.
+    recovering: bool,
+    errors: Vec<Diagnostic>,
 }
+
+/// Default for how deep `generate_ast` may recurse before the expression is rejected, which keeps
+/// inputs such as thousands of nested parentheses from overflowing the stack.
+pub const MAX_NESTING_DEPTH: usize = 128;
+/// Highest nesting limit that can be set. The parser is recursive, and deeper expressions could
+/// overflow the 2 MiB stack of a spawned thread.
+pub const MAX_ALLOWED_DEPTH: usize = 256;
 impl<'a> Parser<'a> {
-    pub fn new(expr: &'a str, old_answer: Option<f64>) -> Result<Self, ParseError> {
+    pub fn new(expr: &'a str, old_answer: Option<Value>) -> Result<Self, ParseError> {
         let mut lexer = Tokenizer::new(expr);
         let cur_token = match lexer.next() {
-            Some(token) => token,
-            None => return Err(ParseError::InvalidOperator("Invalid character".into())),
+            Some(token) => token?,
+            None => Token::Eof,
         };
         Ok(Parser {
             tokenizer: lexer,
             current_token: cur_token,
             previous_token: None,
-            old_answer: old_answer.unwrap_or_default(),
+            old_answer: old_answer.unwrap_or(Value::Number(0.0)),
+            closing_tokens: Vec::new(),
+            depth: 0,
+            max_depth: MAX_NESTING_DEPTH,
+            operators: OperatorTable::default(),
+            recovering: false,
+            errors: Vec::new(),
         })
     }
+    /// A parser for `parse_with_diagnostics`, which unlike `new` can't fail since a malformed
+    /// first token is only recorded.
+    pub fn recovering(expr: &'a str, old_answer: Option<Value>) -> Self {
+        let mut errors = Vec::new();
+        let mut tokenizer = Tokenizer::new(expr);
+        let current_token = loop {
+            match tokenizer.next() {
+                Some(Ok(token)) => break token,
+                Some(Err(error)) => errors.push(Diagnostic {
+                    error,
+                    offset: tokenizer.token_offset(),
+                }),
+                None => break Token::Eof,
+            }
+        };
+        Parser {
+            tokenizer,
+            current_token,
+            previous_token: None,
+            old_answer: old_answer.unwrap_or(Value::Number(0.0)),
+            closing_tokens: Vec::new(),
+            depth: 0,
+            max_depth: MAX_NESTING_DEPTH,
+            operators: OperatorTable::default(),
+            recovering: true,
+            errors,
+        }
+    }
+    /// Change the nesting limit, each level of parentheses or prefix operator takes at least one.
+    /// It's capped at `MAX_ALLOWED_DEPTH`.
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth.min(MAX_ALLOWED_DEPTH);
+        self
+    }
+    /// Read operators with other precedences or associativities than the standard ones.
+    pub fn with_operators(mut self, operators: OperatorTable) -> Self {
+        self.operators = operators;
+        self
+    }
+    pub fn operators(&self) -> &OperatorTable {
+        &self.operators
+    }
+    /// Parse the whole input, stopping at the first error. Tokens left over after a complete
+    /// expression, such as the last parenthesis of `(1 + 2)) * 3`, are an error too.
     pub fn parse(&mut self) -> Result<Node, ParseError> {
-        let ast = self.generate_ast(OperPrec::DefaultZero);
-        match ast {
-            Ok(ast) => Ok(ast),
-            Err(e) => Err(e),
+        let ast = self.generate_ast(OperPrec::DefaultZero)?;
+        if self.current_token != Token::Eof {
+            return Err(self.unexpected_token());
+        }
+        Ok(ast)
+    }
+    /// Parse the whole input even when it has errors, giving every one of them along with the
+    /// tree. Whatever couldn't be parsed is skipped up to the next comma or closing delimiter
+    /// and left as a `Node::Error`, so the tree is only meant to be evaluated without errors.
+    pub fn parse_with_diagnostics(&mut self) -> (Node, Vec<Diagnostic>) {
+        self.recovering = true;
+        let mut ast = self.delimited_ast(OperPrec::DefaultZero);
+        while let Ok(node) = ast {
+            if self.current_token == Token::Eof {
+                ast = Ok(node);
+                break;
+            }
+            // Such as a closing parenthesis that was never opened, the expression goes on after it.
+            let error = self.unexpected_token();
+            self.record(error);
+            ast = match self.get_next_token() {
+                Ok(()) => self.extend_ast(node, OperPrec::DefaultZero),
+                Err(error) => Err(error),
+            };
+            ast = self.recover(ast);
         }
+        let ast = ast.unwrap_or_else(|error| {
+            self.record(error);
+            Node::Error
+        });
+        (ast, std::mem::take(&mut self.errors))
+    }
+}
+impl<'a> Parser<'a> {
+    /// Record `error` as found at the current token, when recovering.
+    fn record(&mut self, error: ParseError) {
+        self.errors.push(Diagnostic {
+            error,
+            offset: self.tokenizer.token_offset(),
+        });
+    }
+    fn unexpected_token(&self) -> ParseError {
+        ParseError::InvalidOperator(format!("Unexpected {:?}", self.current_token))
     }
 }
 impl<'a> Parser<'a> {
     fn get_next_token(&mut self) -> Result<(), ParseError> {
         let next_token = match self.tokenizer.next() {
-            Some(token) => token,
-            None => return Err(ParseError::InvalidOperator("Invalid character".into())),
+            Some(token) => token?,
+            None => Token::Eof,
         };
         self.previous_token = Some(self.current_token.clone());
         self.current_token = next_token;
         Ok(())
     }
     fn generate_ast(&mut self, oper_prec: OperPrec) -> Result<Node, ParseError> {
-        let mut left_expr = self.parse_number()?;
-
-        while oper_prec < self.current_token.get_oper_prec() {
+        if self.depth >= self.max_depth {
+            return Err(ParseError::TooDeeplyNested(self.max_depth));
+        }
+        self.depth += 1;
+        let ast = self.generate_nested_ast(oper_prec);
+        self.depth -= 1;
+        ast
+    }
+    fn generate_nested_ast(&mut self, oper_prec: OperPrec) -> Result<Node, ParseError> {
+        let left_expr = self.parse_number()?;
+        self.extend_ast(left_expr, oper_prec)
+    }
+    /// Apply the operators binding tighter than `oper_prec` to `left_expr`.
+    fn extend_ast(&mut self, mut left_expr: Node, oper_prec: OperPrec) -> Result<Node, ParseError> {
+        while oper_prec < self.current_oper_prec() {
             if self.current_token == Token::Eof {
                 break;
             }
-            let right_expr = self.convert_token_to_node(left_expr.clone())?;
-            left_expr = right_expr;
+            left_expr = self.convert_token_to_node(left_expr)?;
         }
         Ok(left_expr)
     }
+    /// An expression ending at a comma or a closing delimiter, such as an argument, which is
+    /// where parsing can resume after an error.
+    fn delimited_ast(&mut self, oper_prec: OperPrec) -> Result<Node, ParseError> {
+        let open = self.closing_tokens.len();
+        let ast = self.generate_ast(oper_prec);
+        // Delimiters opened within the expression stay open when it fails.
+        self.closing_tokens.truncate(open);
+        self.recover(ast)
+    }
+    /// When recovering, record the error and skip to the next comma or closing delimiter, leaving
+    /// an error node in place of the expression. Too deep a nesting is never recovered from.
+    fn recover(&mut self, ast: Result<Node, ParseError>) -> Result<Node, ParseError> {
+        match ast {
+            Err(error) if self.recovering && !matches!(error, ParseError::TooDeeplyNested(_)) => {
+                self.record(error);
+                self.synchronize(None);
+                Ok(Node::Error)
+            }
+            ast => ast,
+        }
+    }
+    /// When recovering, record an error that doesn't require skipping any token, such as a
+    /// function given the wrong number of arguments.
+    fn recover_in_place(&mut self, ast: Result<Node, ParseError>) -> Result<Node, ParseError> {
+        match ast {
+            Err(error) if self.recovering => {
+                self.record(error);
+                Ok(Node::Error)
+            }
+            ast => ast,
+        }
+    }
+    /// Skip tokens up to `expected`, a comma, a delimiter closing an enclosing expression or the
+    /// end of the input, whichever comes first, none of them being consumed.
+    fn synchronize(&mut self, expected: Option<&Token>) {
+        while self.current_token != Token::Eof
+            && self.current_token != Token::Comma
+            && Some(&self.current_token) != expected
+            && !self.closing_tokens.contains(&self.current_token)
+        {
+            if let Err(error) = self.get_next_token() {
+                self.record(error);
+            }
+        }
+    }
+    /// A bar closes an absolute value when it's the innermost open delimiter, otherwise it's a bitwise or.
+    fn current_oper_prec(&self) -> OperPrec {
+        if self.current_token == Token::Bar && self.closing_tokens.last() == Some(&Token::Bar) {
+            OperPrec::DefaultZero
+        } else {
+            self.operators.precedence(&self.current_token)
+        }
+    }
     fn function_static_arguments(&mut self, n: i32) -> Result<Vec<Node>, ParseError> {
         self.get_next_token()?;
         self.check_paren(Token::LeftParen)?;
+        self.closing_tokens.push(Token::RightParen);
         let mut args = Vec::new();
         for i in 0..n {
-            let arg_expr = self.generate_ast(OperPrec::DefaultZero)?;
+            let arg_expr = self.delimited_ast(OperPrec::DefaultZero)?;
             args.push(arg_expr);
-            if i < n - 1 {
-                self.check_paren(Token::Comma)?;
+            if i < n - 1 && !self.expect(Token::Comma)? {
+                // Missing arguments were already reported.
+                args.extend((i + 1..n).map(|_| Node::Error));
+                break;
+            }
+        }
+        self.closing_tokens.pop();
+        self.check_paren(Token::RightParen)?;
+        Ok(args)
+    }
+    /// Parse the arguments of a function, kept apart from `parse_number` so that nesting other
+    /// tokens doesn't pay for the stack space of every function arm. The node is only built by
+    /// `function_node` once they're all parsed, so that nested calls don't pay for it either.
+    fn parse_function(&mut self, current_function: NativeFunction) -> Result<Node, ParseError> {
+        let args = match current_function {
+            ref function if is_variadic(function) => self.function_arguments()?,
+            NativeFunction::Solve => self.solve_arguments()?,
+            _ => self.function_static_arguments(arity(&current_function))?,
+        };
+        let has_errors = args.contains(&Node::Error);
+        match function_node(current_function, args) {
+            // Whatever is wrong with these arguments was already reported.
+            Err(_) if self.recovering && has_errors => Ok(Node::Error),
+            node => self.recover_in_place(node),
+        }
+    }
+    /// A variable, or a name called like a function that isn't one, kept apart from
+    /// `parse_number` like `parse_function`.
+    fn parse_identifier(&mut self, name: String) -> Result<Node, ParseError> {
+        let offset = self.tokenizer.token_offset();
+        self.get_next_token()?;
+        let is_function_typo = || suggestion::suggest(&name).is_some_and(|s| s.arguments.is_some());
+        if self.current_token == Token::LeftParen && is_function_typo() {
+            return self.unknown_function(name, offset);
+        }
+        self.implicit_multiply(Node::Variable(name))
+    }
+    /// A name called like a function that isn't one, but is close enough to one to be a typo,
+    /// `offset` being where the name starts. When recovering its arguments are still parsed, so
+    /// that their own errors are found and parsing goes on after them.
+    fn unknown_function(&mut self, name: String, offset: usize) -> Result<Node, ParseError> {
+        let error = ParseError::UnknownFunction(name);
+        if !self.recovering {
+            return Err(error);
+        }
+        self.errors.push(Diagnostic { error, offset });
+        self.find_item_list(Token::LeftParen, Token::RightParen, OperPrec::DefaultZero)?;
+        Ok(Node::Error)
+    }
+    fn solve_arguments(&mut self) -> Result<Vec<Node>, ParseError> {
+        self.get_next_token()?;
+        self.check_paren(Token::LeftParen)?;
+        self.closing_tokens.push(Token::RightParen);
+        let equation = self.equation();
+        let mut args = vec![self.recover(equation)?];
+        for _ in 0..2 {
+            if !self.expect(Token::Comma)? {
+                args.push(Node::Error);
+                continue;
             }
+            args.push(self.delimited_ast(OperPrec::DefaultZero)?);
         }
+        self.closing_tokens.pop();
         self.check_paren(Token::RightParen)?;
         Ok(args)
     }
+    /// Either `lhs = rhs`, read as `lhs - rhs`, or an expression equated to zero.
+    fn equation(&mut self) -> Result<Node, ParseError> {
+        let lhs = self.generate_ast(OperPrec::DefaultZero)?;
+        if self.current_token != Token::EqualSign {
+            return Ok(lhs);
+        }
+        self.get_next_token()?;
+        let rhs = self.generate_ast(OperPrec::DefaultZero)?;
+        Ok(Node::Subtract(Box::new(lhs), Box::new(rhs)))
+    }
     fn function_arguments(&mut self) -> Result<Vec<Node>, ParseError> {
+        self.get_next_token()?;
         self.find_item_list(Token::LeftParen, Token::RightParen, OperPrec::DefaultZero)
     }
     fn find_item_list(
@@ -77,15 +317,15 @@ impl<'a> Parser<'a> {
         end_token: Token,
         oper_prec: OperPrec,
     ) -> Result<Vec<Node>, ParseError> {
-        self.get_next_token()?;
         self.check_paren(start_token)?;
+        self.closing_tokens.push(end_token.clone());
         let mut args = Vec::new();
         loop {
             if args.is_empty() && (end_token == self.current_token) {
                 self.get_next_token()?;
                 break;
             }
-            let arg_expr = self.generate_ast(oper_prec.clone())?;
+            let arg_expr = self.delimited_ast(oper_prec)?;
             args.push(arg_expr);
             if Token::Comma == self.current_token {
                 self.get_next_token()?;
@@ -93,14 +333,29 @@ impl<'a> Parser<'a> {
                 self.get_next_token()?;
                 break;
             } else {
-                return Err(ParseError::InvalidOperator(format!(
+                let error = ParseError::InvalidOperator(format!(
                     "Expected either {:?} or {:?}, got {:?}",
                     Token::Comma,
                     end_token,
                     self.current_token
-                )));
+                ));
+                if !self.recovering {
+                    return Err(error);
+                }
+                self.record(error);
+                self.synchronize(Some(&end_token));
+                match &self.current_token {
+                    Token::Comma => self.get_next_token()?,
+                    token if *token == end_token => {
+                        self.get_next_token()?;
+                        break;
+                    }
+                    // The list is never closed, which was reported.
+                    _ => break,
+                }
             }
         }
+        self.closing_tokens.pop();
         Ok(args)
     }
     fn parse_number(&mut self) -> Result<Node, ParseError> {
@@ -108,130 +363,44 @@ impl<'a> Parser<'a> {
         match token {
             Token::Ans => {
                 self.get_next_token()?;
-                Ok(Node::Number(self.old_answer))
+                Ok(Node::from(self.old_answer.clone()))
+            }
+            Token::Boolean(boolean) => {
+                self.get_next_token()?;
+                Ok(Node::Boolean(boolean))
+            }
+            Token::Not => {
+                let expr = self.operand(Fixity::Prefix)?;
+                Ok(Node::Not(Box::new(expr)))
             }
             Token::ExplicitFunction(current_function) => {
-                let current_function = match current_function {
-                    NativeFunction::Abs => {
-                        Node::Abs(Box::new(self.function_static_arguments(1)?[0].clone()))
-                    }
-                    NativeFunction::Floor => {
-                        Node::Floor(Box::new(self.function_static_arguments(1)?[0].clone()))
-                    }
-                    NativeFunction::Ceil => {
-                        Node::Ceil(Box::new(self.function_static_arguments(1)?[0].clone()))
-                    }
-                    NativeFunction::Round => {
-                        Node::Round(Box::new(self.function_static_arguments(1)?[0].clone()))
-                    }
-                    NativeFunction::Sin => {
-                        Node::Sin(Box::new(self.function_static_arguments(1)?[0].clone()))
-                    }
-                    NativeFunction::Cos => {
-                        Node::Cos(Box::new(self.function_static_arguments(1)?[0].clone()))
-                    }
-                    NativeFunction::Tan => {
-                        Node::Tan(Box::new(self.function_static_arguments(1)?[0].clone()))
-                    }
-                    NativeFunction::Sinh => {
-                        Node::Sinh(Box::new(self.function_static_arguments(1)?[0].clone()))
-                    }
-                    NativeFunction::Cosh => {
-                        Node::Cosh(Box::new(self.function_static_arguments(1)?[0].clone()))
-                    }
-                    NativeFunction::Tanh => {
-                        Node::Tanh(Box::new(self.function_static_arguments(1)?[0].clone()))
-                    }
-                    NativeFunction::Asin => {
-                        Node::Asin(Box::new(self.function_static_arguments(1)?[0].clone()))
-                    }
-                    NativeFunction::Acos => {
-                        Node::Acos(Box::new(self.function_static_arguments(1)?[0].clone()))
-                    }
-                    NativeFunction::Atan => {
-                        Node::Atan(Box::new(self.function_static_arguments(1)?[0].clone()))
-                    }
-                    NativeFunction::Arsinh => {
-                        Node::Arsinh(Box::new(self.function_static_arguments(1)?[0].clone()))
-                    }
-                    NativeFunction::Arcosh => {
-                        Node::Arcosh(Box::new(self.function_static_arguments(1)?[0].clone()))
-                    }
-                    NativeFunction::Artanh => {
-                        Node::Artanh(Box::new(self.function_static_arguments(1)?[0].clone()))
-                    }
-                    NativeFunction::Sqrt => {
-                        Node::Sqrt(Box::new(self.function_static_arguments(1)?[0].clone()))
-                    }
-                    NativeFunction::Exp => {
-                        Node::Exp(Box::new(self.function_static_arguments(1)?[0].clone()))
-                    }
-                    NativeFunction::Exp2 => {
-                        Node::Exp2(Box::new(self.function_static_arguments(1)?[0].clone()))
-                    }
-                    NativeFunction::Ln => {
-                        Node::Ln(Box::new(self.function_static_arguments(1)?[0].clone()))
-                    }
-                    NativeFunction::Sign => {
-                        Node::Sign(Box::new(self.function_static_arguments(1)?[0].clone()))
-                    }
-                    NativeFunction::Truncate => {
-                        Node::Truncate(Box::new(self.function_static_arguments(1)?[0].clone()))
-                    }
-                    NativeFunction::Atan2 => {
-                        let args = self.function_static_arguments(2)?;
-                        Node::Atan2(Box::new(args[0].clone()), Box::new(args[1].clone()))
-                    }
-                    NativeFunction::Pow => {
-                        let args = self.function_static_arguments(2)?;
-                        Node::Pow(Box::new(args[0].clone()), Box::new(args[1].clone()))
-                    }
-                    NativeFunction::Log => {
-                        let args = self.function_static_arguments(2)?;
-                        Node::Log(Box::new(args[0].clone()), Box::new(args[1].clone()))
-                    }
-                    NativeFunction::Min => {
-                        let args = self.function_arguments()?;
-                        if args.is_empty() {
-                            return Err(ParseError::UnableToParse(
-                                "There's no arguments in the min function".to_string(),
-                            ));
-                        }
-                        Node::Min(args)
-                    }
-                    NativeFunction::Max => {
-                        let args = self.function_arguments()?;
-                        if args.is_empty() {
-                            return Err(ParseError::UnableToParse(
-                                "There's no arguments in the max function".to_string(),
-                            ));
-                        }
-                        Node::Max(args)
-                    }
-                };
-                self.implicit_multiply(current_function)
+                let node = self.parse_function(current_function)?;
+                self.implicit_multiply(node)
             }
             Token::Subtract => {
-                self.get_next_token()?;
-                let expr = self.generate_ast(OperPrec::Negative)?;
+                let expr = self.operand(Fixity::Prefix)?;
                 Ok(Node::Negative(Box::new(expr)))
             }
             Token::Add => {
-                self.get_next_token()?;
-                let expr = self.generate_ast(OperPrec::Negative)?;
+                let expr = self.operand(Fixity::Prefix)?;
                 Ok(expr)
             }
+            Token::Tilde => {
+                let expr = self.operand(Fixity::Prefix)?;
+                Ok(Node::BitNot(Box::new(expr)))
+            }
             Token::Num(i) => {
                 self.get_next_token()?;
                 self.implicit_multiply(Node::Number(i))
             }
-            Token::Pi => {
-                self.get_next_token()?;
-                Ok(Node::Number(std::f64::consts::PI))
-            }
-            Token::E => {
-                self.get_next_token()?;
-                Ok(Node::Number(std::f64::consts::E))
+            Token::Identifier(name) => self.parse_identifier(name),
+            Token::LeftBracket => {
+                let items = self.find_item_list(
+                    Token::LeftBracket,
+                    Token::RightBracket,
+                    OperPrec::DefaultZero,
+                )?;
+                Ok(Node::List(items))
             }
             Token::LeftParen => self.get_enclosed_elements_with_impl_mult(
                 OperPrec::DefaultZero,
@@ -259,13 +428,27 @@ impl<'a> Parser<'a> {
         }
     }
     fn implicit_multiply(&mut self, node: Node) -> Result<Node, ParseError> {
+        // `1 2` is more likely a typo than a product.
+        if let (Some(previous @ (Token::Num(_) | Token::Identifier(_))), Token::Num(_)) =
+            (&self.previous_token, &self.current_token)
+        {
+            return Err(ParseError::InvalidOperator(format!(
+                "Expected an operator between {:?} and {:?}",
+                previous, self.current_token
+            )));
+        }
         if (self.current_token == Token::LeftParen)
             || (self.current_token == Token::LeftCeiling)
             || (self.current_token == Token::LeftFloor)
             || matches!(self.current_token, Token::ExplicitFunction(_))
             || matches!(self.current_token, Token::Num(_))
+            || matches!(self.current_token, Token::Identifier(_))
         {
-            let right = self.generate_ast(OperPrec::MulDiv)?;
+            // Juxtaposition binds like `*`, so that `2x^2` is `2 * x^2`.
+            let oper_prec = self
+                .operators
+                .operand_precedence(&Token::Multiply, Fixity::Infix);
+            let right = self.generate_ast(oper_prec)?;
             return Ok(Node::Multiply(Box::new(node), Box::new(right)));
         }
         Ok(node)
@@ -277,52 +460,70 @@ impl<'a> Parser<'a> {
         get_node: fn(Node) -> Node,
     ) -> Result<Node, ParseError> {
         self.get_next_token()?;
-        let expr = self.generate_ast(oper_prec)?;
+        self.closing_tokens.push(end_token.clone());
+        let expr = self.delimited_ast(oper_prec)?;
+        self.closing_tokens.pop();
         self.check_paren(end_token)?;
         self.implicit_multiply(get_node(expr))
     }
     fn check_paren(&mut self, expected: Token) -> Result<(), ParseError> {
+        self.expect(expected).map(|_| ())
+    }
+    /// Consume `expected`. When recovering, a mismatch is recorded and the tokens before
+    /// `expected` are skipped, unless a comma or a closing delimiter comes first, in which case
+    /// `false` is returned.
+    fn expect(&mut self, expected: Token) -> Result<bool, ParseError> {
         if expected == self.current_token {
             self.get_next_token()?;
-            Ok(())
-        } else {
-            Err(ParseError::InvalidOperator(format!(
-                "Expected {:?}, got {:?}",
-                expected, self.current_token
-            )))
+            return Ok(true);
+        }
+        let error = ParseError::InvalidOperator(format!(
+            "Expected {:?}, got {:?}",
+            expected, self.current_token
+        ));
+        if !self.recovering {
+            return Err(error);
+        }
+        self.record(error);
+        self.synchronize(Some(&expected));
+        if expected == self.current_token {
+            self.get_next_token()?;
+            return Ok(true);
         }
+        Ok(false)
+    }
+    /// Consume the operator at hand and parse the operand following it.
+    fn operand(&mut self, fixity: Fixity) -> Result<Node, ParseError> {
+        let oper_prec = self
+            .operators
+            .operand_precedence(&self.current_token, fixity);
+        self.get_next_token()?;
+        self.generate_ast(oper_prec)
     }
     fn convert_token_to_node(&mut self, left_expr: Node) -> Result<Node, ParseError> {
+        // Binary operators are kept out of the match below, whose frame is paid for by every
+        // level of nesting.
+        if let Some(node) = binary_node(&self.current_token) {
+            let right_expr = self.operand(Fixity::Infix)?;
+            return Ok(node(Box::new(left_expr), Box::new(right_expr)));
+        }
         match self.current_token {
-            Token::Add => {
-                self.get_next_token()?;
-                let right_expr = self.generate_ast(OperPrec::AddSub)?;
-                Ok(Node::Add(Box::new(left_expr), Box::new(right_expr)))
-            }
-            Token::Subtract => {
+            Token::LeftBracket => {
                 self.get_next_token()?;
-                let right_expr = self.generate_ast(OperPrec::AddSub)?;
-                Ok(Node::Subtract(Box::new(left_expr), Box::new(right_expr)))
-            }
-            Token::Multiply => {
-                self.get_next_token()?;
-                let right_expr = self.generate_ast(OperPrec::MulDiv)?;
-                Ok(Node::Multiply(Box::new(left_expr), Box::new(right_expr)))
-            }
-            Token::Divide => {
-                self.get_next_token()?;
-                let right_expr = self.generate_ast(OperPrec::MulDiv)?;
-                Ok(Node::Divide(Box::new(left_expr), Box::new(right_expr)))
-            }
-            Token::Caret => {
-                self.get_next_token()?;
-                let right_expr = self.generate_ast(OperPrec::Power)?;
-                Ok(Node::Caret(Box::new(left_expr), Box::new(right_expr)))
+                self.closing_tokens.push(Token::RightBracket);
+                let index = self.delimited_ast(OperPrec::DefaultZero)?;
+                self.closing_tokens.pop();
+                self.check_paren(Token::RightBracket)?;
+                Ok(Node::Index(Box::new(left_expr), Box::new(index)))
             }
             Token::ExclamationMark => {
                 self.get_next_token()?;
                 self.implicit_multiply(Node::Factorial(Box::new(left_expr)))
             }
+            Token::DoubleExclamationMark => {
+                self.get_next_token()?;
+                self.implicit_multiply(Node::DoubleFactorial(Box::new(left_expr)))
+            }
             Token::DegToRad => {
                 self.get_next_token()?;
                 Ok(Node::Multiply(
@@ -345,10 +546,16 @@ impl<'a> Parser<'a> {
                 self.get_next_token()?;
                 Ok(Node::Pow3(Box::new(left_expr)))
             }
-            Token::Modulo => {
+            Token::Question => {
                 self.get_next_token()?;
-                let right_expr = self.generate_ast(OperPrec::MulDiv)?;
-                Ok(Node::Modulo(Box::new(left_expr), Box::new(right_expr)))
+                let then_expr = self.generate_ast(OperPrec::DefaultZero)?;
+                self.check_paren(Token::Colon)?;
+                let else_expr = self.generate_ast(OperPrec::DefaultZero)?;
+                Ok(Node::If(
+                    Box::new(left_expr),
+                    Box::new(then_expr),
+                    Box::new(else_expr),
+                ))
             }
             _ => Err(ParseError::InvalidOperator(format!(
                 "Please enter a valid operator {:?}",
@@ -358,10 +565,398 @@ impl<'a> Parser<'a> {
     }
 }
 
-#[derive(Debug)]
+type BinaryNode = fn(Box<Node>, Box<Node>) -> Node;
+
+/// Constructor of the node of a binary operator.
+fn binary_node(token: &Token) -> Option<BinaryNode> {
+    let node = match token {
+        Token::Add => Node::Add,
+        Token::Subtract => Node::Subtract,
+        Token::Multiply => Node::Multiply,
+        Token::Divide => Node::Divide,
+        Token::Backslash => Node::LeftDivide,
+        Token::Caret => Node::Caret,
+        Token::Range => Node::Range,
+        Token::Modulo => Node::Modulo,
+        Token::IntegerDivide => Node::IntegerDivide,
+        Token::Less => Node::Less,
+        Token::LessEqual => Node::LessEqual,
+        Token::Greater => Node::Greater,
+        Token::GreaterEqual => Node::GreaterEqual,
+        Token::Equal => Node::Equal,
+        Token::NotEqual => Node::NotEqual,
+        Token::And => Node::And,
+        Token::Or => Node::Or,
+        Token::Ampersand => Node::BitAnd,
+        Token::Bar => Node::BitOr,
+        Token::Xor => Node::BitXor,
+        Token::ShiftLeft => Node::ShiftLeft,
+        Token::ShiftRight => Node::ShiftRight,
+        _ => return None,
+    };
+    Some(node)
+}
+
+/// Number of arguments of the functions taking a fixed number of them.
+fn arity(function: &NativeFunction) -> i32 {
+    use self::NativeFunction::*;
+    match function {
+        Atan2 | Pow | Log | Beta | BesselJ | BesselY | BesselI | BesselK | GammaInc | GammaIncC
+        | Binomial | Permutations | ModInv | RandInt => 2,
+        If | BetaInc | ModPow | Solve | Derivative | Limit | LimitLeft | LimitRight => 3,
+        Sum | Product | Integrate | Root => 4,
+        Distribution(distribution, _) => distribution.parameter_count() as i32 + 1,
+        Sample(distribution) => distribution.parameter_count() as i32,
+        Rand => 0,
+        _ => 1,
+    }
+}
+
+/// Whether `function` takes a varying number of arguments.
+fn is_variadic(function: &NativeFunction) -> bool {
+    use self::NativeFunction::*;
+    matches!(
+        function,
+        Sum | Min | Max | Piecewise | Roots | Gcd | Lcm | Financial(_)
+    ) || is_statistic(function)
+}
+
+/// How many arguments `function` expects, as in "takes 2 arguments".
+pub fn expected_arguments(function: &NativeFunction) -> String {
+    use self::NativeFunction::*;
+    let count = match function {
+        Financial(financial) => {
+            match (financial.arguments().start(), financial.arguments().end()) {
+                (start, &usize::MAX) => return format!("at least {} arguments", start),
+                (start, end) if start == end => *start,
+                (start, end) => return format!("{} or {} arguments", start, end),
+            }
+        }
+        Roots => return "1 or 2 arguments".to_string(),
+        Piecewise => return "an odd number of arguments".to_string(),
+        Quantile => return "at least 2 arguments".to_string(),
+        function if is_variadic(function) => return "at least 1 argument".to_string(),
+        function => arity(function) as usize,
+    };
+    format!("{} argument{}", count, if count == 1 { "" } else { "s" })
+}
+
+/// Whether `function` is a statistic taking any number of values, lists included.
+fn is_statistic(function: &NativeFunction) -> bool {
+    use self::NativeFunction::*;
+    matches!(
+        function,
+        Mean | Median
+            | Mode
+            | Variance
+            | PopulationVariance
+            | StandardDeviation
+            | PopulationStandardDeviation
+            | Quantile
+            | GeometricMean
+            | HarmonicMean
+            | Skewness
+            | Kurtosis
+    )
+}
+
+/// Build the node of a function from its parsed arguments, checking that they fit it.
+fn function_node(function: NativeFunction, args: Vec<Node>) -> Result<Node, ParseError> {
+    use self::NativeFunction::*;
+    let node = match function {
+        Abs => Node::Abs(single(args)?),
+        Floor => Node::Floor(single(args)?),
+        Ceil => Node::Ceil(single(args)?),
+        Round => Node::Round(single(args)?),
+        Sin => Node::Sin(single(args)?),
+        Cos => Node::Cos(single(args)?),
+        Tan => Node::Tan(single(args)?),
+        Sinh => Node::Sinh(single(args)?),
+        Cosh => Node::Cosh(single(args)?),
+        Tanh => Node::Tanh(single(args)?),
+        Asin => Node::Asin(single(args)?),
+        Acos => Node::Acos(single(args)?),
+        Atan => Node::Atan(single(args)?),
+        Arsinh => Node::Arsinh(single(args)?),
+        Arcosh => Node::Arcosh(single(args)?),
+        Artanh => Node::Artanh(single(args)?),
+        Sqrt => Node::Sqrt(single(args)?),
+        Exp => Node::Exp(single(args)?),
+        Exp2 => Node::Exp2(single(args)?),
+        Ln => Node::Ln(single(args)?),
+        Sign => Node::Sign(single(args)?),
+        Truncate => Node::Truncate(single(args)?),
+        Len => Node::Len(single(args)?),
+        Gamma => Node::Gamma(single(args)?),
+        LnGamma => Node::LnGamma(single(args)?),
+        Digamma => Node::Digamma(single(args)?),
+        Erf => Node::Erf(single(args)?),
+        Erfc => Node::Erfc(single(args)?),
+        ErfInv => Node::ErfInv(single(args)?),
+        Zeta => Node::Zeta(single(args)?),
+        LambertW => Node::LambertW(single(args)?),
+        Transpose => Node::Transpose(single(args)?),
+        Determinant => Node::Determinant(single(args)?),
+        Inverse => Node::Inverse(single(args)?),
+        Identity => Node::Identity(single(args)?),
+        Trace => Node::Trace(single(args)?),
+        Rank => Node::Rank(single(args)?),
+        Eigenvalues => Node::Eigenvalues(single(args)?),
+        IsPrime => Node::IsPrime(single(args)?),
+        NextPrime => Node::NextPrime(single(args)?),
+        Factor => Node::Factor(single(args)?),
+        Totient => Node::Totient(single(args)?),
+        Fibonacci => Node::Fibonacci(single(args)?),
+        Subfactorial => Node::Subfactorial(single(args)?),
+        Binomial | Permutations => {
+            let [n, k] = boxed(args)?;
+            if function == Binomial {
+                Node::Binomial(n, k)
+            } else {
+                Node::Permutations(n, k)
+            }
+        }
+        ModPow => {
+            let [base, exponent, modulus] = boxed(args)?;
+            Node::ModPow(base, exponent, modulus)
+        }
+        ModInv => {
+            let [a, modulus] = boxed(args)?;
+            Node::ModInv(a, modulus)
+        }
+        Gcd => Node::Gcd(values(args, &function)?),
+        Rand => {
+            let [] = boxed(args)?;
+            Node::Rand
+        }
+        RandInt => {
+            let [low, high] = boxed(args)?;
+            Node::RandInt(low, high)
+        }
+        Choice => Node::Choice(single(args)?),
+        Lcm => Node::Lcm(values(args, &function)?),
+        Beta | GammaInc | GammaIncC => {
+            let [a, x] = boxed(args)?;
+            match function {
+                Beta => Node::Beta(a, x),
+                GammaInc => Node::GammaInc(a, x),
+                _ => Node::GammaIncC(a, x),
+            }
+        }
+        BetaInc => {
+            let [a, b, x] = boxed(args)?;
+            Node::BetaInc(a, b, x)
+        }
+        BesselJ | BesselY | BesselI | BesselK => {
+            let [order, x] = boxed(args)?;
+            match function {
+                BesselJ => Node::BesselJ(order, x),
+                BesselY => Node::BesselY(order, x),
+                BesselI => Node::BesselI(order, x),
+                _ => Node::BesselK(order, x),
+            }
+        }
+        Atan2 => {
+            let [y, x] = boxed(args)?;
+            Node::Atan2(y, x)
+        }
+        Pow => {
+            let [base, exponent] = boxed(args)?;
+            Node::Pow(base, exponent)
+        }
+        Log => {
+            let [x, base] = boxed(args)?;
+            Node::Log(x, base)
+        }
+        If => {
+            let [condition, then_expr, else_expr] = boxed(args)?;
+            Node::If(condition, then_expr, else_expr)
+        }
+        Piecewise => {
+            if args.len().is_multiple_of(2) {
+                return Err(ParseError::UnableToParse(
+                    "The piecewise function expects pairs of condition and value followed by a default value".to_string(),
+                ));
+            }
+            Node::Piecewise(args)
+        }
+        Sum if !is_series(&args) => Node::Total(values(args, &function)?),
+        Sum | Product => {
+            let [variable, from, to, body] = boxed(args)?;
+            let variable = bound_variable(&variable, &function)?;
+            if function == Sum {
+                Node::Sum(variable, from, to, body)
+            } else {
+                Node::Product(variable, from, to, body)
+            }
+        }
+        Integrate | Root => {
+            let [body, variable, from, to] = boxed(args)?;
+            let variable = bound_variable(&variable, &function)?;
+            if function == Integrate {
+                Node::Integrate(variable, from, to, body)
+            } else {
+                Node::Root(variable, from, to, body)
+            }
+        }
+        Solve => {
+            let [equation, variable, guess] = boxed(args)?;
+            Node::Solve(bound_variable(&variable, &function)?, guess, equation)
+        }
+        Derivative | Limit | LimitLeft | LimitRight => {
+            let [function_expr, variable, at] = boxed(args)?;
+            let variable = bound_variable(&variable, &function)?;
+            match function {
+                Derivative => Node::Derivative(variable, at, function_expr),
+                Limit => Node::Limit(variable, at, function_expr),
+                LimitLeft => Node::LimitLeft(variable, at, function_expr),
+                _ => Node::LimitRight(variable, at, function_expr),
+            }
+        }
+        Roots => {
+            let mut args = args.into_iter();
+            let (polynomial, variable) = (args.next(), args.next());
+            let (Some(polynomial), None) = (polynomial, args.next()) else {
+                return Err(ParseError::UnableToParse(
+                    "The roots function expects a polynomial, optionally followed by its variable"
+                        .to_string(),
+                ));
+            };
+            let variable = match variable {
+                Some(variable) => bound_variable(&variable, &function)?,
+                // Constants can be the variable too, but only when there's no other name.
+                None => match (&polynomial.unknowns()[..], &polynomial.free_variables()[..]) {
+                    ([variable], _) | ([], [variable]) => variable.clone(),
+                    _ => {
+                        return Err(ParseError::UnableToParse(
+                            "The variable of the polynomial has to be given, as in roots(p, x)"
+                                .to_string(),
+                        ))
+                    }
+                },
+            };
+            Node::Roots(variable, Box::new(polynomial))
+        }
+        Quantile if args.len() < 2 => {
+            return Err(ParseError::UnableToParse(
+                "The quantile function expects values followed by the order of the quantile"
+                    .to_string(),
+            ))
+        }
+        Mean => Node::Mean(values(args, &function)?),
+        Median => Node::Median(values(args, &function)?),
+        Mode => Node::Mode(values(args, &function)?),
+        Variance => Node::Variance(values(args, &function)?),
+        PopulationVariance => Node::PopulationVariance(values(args, &function)?),
+        StandardDeviation => Node::StandardDeviation(values(args, &function)?),
+        PopulationStandardDeviation => Node::PopulationStandardDeviation(values(args, &function)?),
+        Quantile => Node::Quantile(values(args, &function)?),
+        GeometricMean => Node::GeometricMean(values(args, &function)?),
+        HarmonicMean => Node::HarmonicMean(values(args, &function)?),
+        Skewness => Node::Skewness(values(args, &function)?),
+        Kurtosis => Node::Kurtosis(values(args, &function)?),
+        Distribution(distribution, distribution_function) => {
+            Node::Distribution(distribution, distribution_function, args)
+        }
+        Sample(distribution) => Node::Sample(distribution, args),
+        Financial(financial) => {
+            financial
+                .check_arguments(args.len())
+                .map_err(|error| ParseError::UnableToParse(error.to_string()))?;
+            Node::Financial(financial, args)
+        }
+        Min | Max => {
+            if args.is_empty() {
+                return Err(ParseError::UnableToParse(format!(
+                    "There's no arguments in the {:?} function",
+                    function
+                )));
+            }
+            if function == Min {
+                Node::Min(args)
+            } else {
+                Node::Max(args)
+            }
+        }
+    };
+    Ok(node)
+}
+
+fn boxed<const N: usize>(args: Vec<Node>) -> Result<[Box<Node>; N], ParseError> {
+    let args: [Node; N] = args.try_into().map_err(|args: Vec<Node>| {
+        ParseError::UnableToParse(format!("Expected {} arguments, got {}", N, args.len()))
+    })?;
+    Ok(args.map(Box::new))
+}
+
+/// Arguments of a function taking any number of values, at least one being needed.
+fn values(args: Vec<Node>, function: &NativeFunction) -> Result<Vec<Node>, ParseError> {
+    if args.is_empty() {
+        return Err(ParseError::UnableToParse(format!(
+            "There's no arguments in the {:?} function",
+            function
+        )));
+    }
+    Ok(args)
+}
+
+/// Whether the arguments of `sum` are those of a series, `sum(k, from, to, expr)`, rather than
+/// values to add up.
+fn is_series(args: &[Node]) -> bool {
+    matches!(args, [Node::Variable(_), _, _, _])
+}
+
+fn single(args: Vec<Node>) -> Result<Box<Node>, ParseError> {
+    let [arg] = boxed(args)?;
+    Ok(arg)
+}
+
+/// Name of the variable a function such as `sum` or `integrate` binds, given as a plain identifier.
+fn bound_variable(arg: &Node, function: &NativeFunction) -> Result<String, ParseError> {
+    match arg {
+        Node::Variable(variable) => Ok(variable.clone()),
+        _ => Err(ParseError::UnableToParse(format!(
+            "{:?} expects the name of a variable, got {:?}",
+            function, arg
+        ))),
+    }
+}
+
+#[derive(Debug, PartialEq)]
 pub enum ParseError {
     UnableToParse(String),
     InvalidOperator(String),
+    UnknownCharacter(char),
+    MalformedNumber(String),
+    TooDeeplyNested(usize),
+    /// A name called like a function, close to the name of one. The function it's probably a
+    /// typo of is only looked up again when needed, see `suggestion`, so that the error stays as
+    /// small as the others: every level of the recursive parser holds one.
+    UnknownFunction(String),
+}
+
+/// A syntax error found by `Parser::parse_with_diagnostics`, along with where it is.
+#[derive(Debug, PartialEq)]
+pub struct Diagnostic {
+    pub error: ParseError,
+    /// Byte offset in the input of the token the error was found at.
+    pub offset: usize,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} at byte {}", self.error, self.offset)
+    }
+}
+
+impl ParseError {
+    /// The function an unknown one is probably a typo of.
+    pub fn suggestion(&self) -> Option<Suggestion> {
+        match self {
+            ParseError::UnknownFunction(name) => suggestion::suggest(name),
+            _ => None,
+        }
+    }
 }
 
 impl fmt::Display for ParseError {
@@ -369,25 +964,376 @@ impl fmt::Display for ParseError {
         let message = match &self {
             self::ParseError::UnableToParse(e) => e.clone(),
             self::ParseError::InvalidOperator(e) => e.clone(),
+            self::ParseError::UnknownCharacter(c) => format!("unknown character {:?}", c),
+            self::ParseError::MalformedNumber(number) => format!("malformed number {}", number),
+            self::ParseError::TooDeeplyNested(depth) => {
+                format!("expression nested deeper than {} levels", depth)
+            }
+            self::ParseError::UnknownFunction(name) => match suggestion::suggest(name) {
+                Some(suggestion) => format!("unknown function {}, {}", name, suggestion),
+                None => format!("unknown function {}", name),
+            },
         };
         write!(f, "Error in evaluating {}", message)
     }
 }
 
-impl std::convert::From<std::boxed::Box<dyn std::error::Error>> for ParseError {
-    fn from(_evalerr: std::boxed::Box<dyn std::error::Error>) -> Self {
-        ParseError::UnableToParse("Unable to parse".into())
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::calcparse::ast::Node::{Add, Number};
+    use proptest::prelude::*;
     #[test]
     fn test_addition() {
         let mut parser = Parser::new("1+2", None).unwrap();
         let expected = Add(Box::new(Number(1.0)), Box::new(Number(2.0)));
         assert_eq!(parser.parse().unwrap(), expected);
     }
+    #[test]
+    fn test_juxtaposed_numbers() {
+        let parse = |expr| Parser::new(expr, None).unwrap().parse();
+        assert!(parse("1 2").is_err());
+        assert!(parse("x 2").is_err());
+        assert_eq!(parse("(1)2"), parse("1*2"));
+        assert_eq!(parse("2 x"), parse("2*x"));
+    }
+    #[test]
+    fn test_deeply_nested_parentheses() {
+        let expr = format!("{}1{}", "(".repeat(100_000), ")".repeat(100_000));
+        let mut parser = Parser::new(&expr, None).unwrap();
+        assert_eq!(
+            parser.parse(),
+            Err(ParseError::TooDeeplyNested(MAX_NESTING_DEPTH))
+        );
+        let expr = format!("{}1{}", "(".repeat(50), ")".repeat(50));
+        assert_eq!(Parser::new(&expr, None).unwrap().parse(), Ok(Number(1.0)));
+    }
+    #[test]
+    fn test_unknown_function() {
+        let unknown = |name: &str| ParseError::UnknownFunction(name.to_string());
+        let parse = |expr| Parser::new(expr, None).unwrap().parse();
+        assert_eq!(parse("sqr(2, 3)"), Err(unknown("sqr")));
+        assert_eq!(parse("1 + sqr(2)"), Err(unknown("sqr")));
+        assert_eq!(
+            unknown("sqr").suggestion(),
+            Some(Suggestion {
+                name: "sqrt",
+                arguments: Some("1 argument".to_string())
+            })
+        );
+        assert_eq!(
+            unknown("sine").to_string(),
+            "Error in evaluating unknown function sine, did you mean `sin`? It takes 1 argument"
+        );
+        // Short names and names far from any function are variables multiplied by what follows.
+        assert!(parse("x(2)").is_ok());
+        assert!(parse("velocity(2)").is_ok());
+        assert!(parse("sqr + 1").is_ok());
+        let (ast, errors) =
+            Parser::recovering("1 + sqr(2, 3) * sine(4 +, 5)", None).parse_with_diagnostics();
+        assert_eq!(
+            errors,
+            [
+                Diagnostic {
+                    error: unknown("sqr"),
+                    offset: 4
+                },
+                Diagnostic {
+                    error: unknown("sine"),
+                    offset: 16
+                },
+                Diagnostic {
+                    error: ParseError::UnableToParse(
+                        "Unknown parsing token for parsing number".to_string()
+                    ),
+                    offset: 24
+                },
+            ]
+        );
+        assert!(matches!(ast, Node::Add(..)));
+    }
+    #[test]
+    fn test_trailing_tokens() {
+        let parse = |expr| Parser::new(expr, None).unwrap().parse();
+        assert_eq!(
+            parse("(1 + 2)) * 3"),
+            Err(ParseError::InvalidOperator(
+                "Unexpected RightParen".to_string()
+            ))
+        );
+        assert!(parse("1 + 2]").is_err());
+        assert!(parse("(1 + 2) * 3").is_ok());
+    }
+    #[test]
+    fn test_error_recovery() {
+        use crate::calcparse::ast::Node::*;
+
+        let parse = |expr| Parser::recovering(expr, None).parse_with_diagnostics();
+        let at = |error, offset| Diagnostic { error, offset };
+        let (ast, errors) = parse("[1 +, 2, * 3]");
+        assert_eq!(ast, List(vec![Error, Number(2.0), Error]));
+        assert_eq!(errors.len(), 2);
+        let (ast, errors) = parse("atan2(1) + sin(2 #)");
+        let atan2 = Atan2(Box::new(Number(1.0)), Box::new(Error));
+        assert_eq!(ast, Add(Box::new(atan2), Box::new(Sin(Box::new(Error)))));
+        assert_eq!(
+            errors,
+            [
+                at(
+                    ParseError::InvalidOperator("Expected Comma, got RightParen".to_string()),
+                    7
+                ),
+                at(ParseError::UnknownCharacter('#'), 17),
+            ]
+        );
+        let (ast, errors) = parse("(1 + 2)) * 3");
+        let sum = Add(Box::new(Number(1.0)), Box::new(Number(2.0)));
+        assert_eq!(ast, Multiply(Box::new(sum.clone()), Box::new(Number(3.0))));
+        assert_eq!(
+            errors,
+            [at(
+                ParseError::InvalidOperator("Unexpected RightParen".to_string()),
+                7
+            )]
+        );
+        let (ast, errors) = parse("#1 + 2");
+        assert_eq!(
+            (ast, errors),
+            (sum, vec![at(ParseError::UnknownCharacter('#'), 0)])
+        );
+        let (ast, errors) = parse("max(integrate(x, 2, 0, 1), 3, (4");
+        assert_eq!(ast, Max(vec![Error, Number(3.0), Number(4.0)]));
+        assert_eq!(errors.len(), 3);
+        // Offsets count bytes, whitespace and multibyte characters included.
+        let (_, errors) = parse("π +\n  2 )");
+        assert_eq!(
+            errors,
+            [at(
+                ParseError::InvalidOperator("Unexpected RightParen".to_string()),
+                9
+            )]
+        );
+        let (ast, errors) = parse("sin(x)^2 + 1");
+        assert!(errors.is_empty());
+        assert_eq!(
+            ast,
+            Parser::new("sin(x)^2 + 1", None).unwrap().parse().unwrap()
+        );
+    }
+    #[test]
+    fn test_precedence() {
+        let parse = |expr| Parser::new(expr, None).unwrap().parse().unwrap();
+        let rules = [
+            // `^` is right associative and binds tighter than the unary minus.
+            ("2^3^2", "2^(3^2)"),
+            ("-2^2", "-(2^2)"),
+            ("-x^2", "-(x^2)"),
+            ("-2²", "-(2²)"),
+            ("2^-3", "2^(-3)"),
+            ("2^-3^2", "2^(-(3^2))"),
+            ("2^3²", "2^(3²)"),
+            ("2^3!", "2^(3!)"),
+            ("-3!", "-(3!)"),
+            // The other binary operators are left associative.
+            ("1-2-3", "(1-2)-3"),
+            ("8/4/2", "(8/4)/2"),
+            ("8//4%3", "(8//4)%3"),
+            ("1..2..3", "(1..2)..3"),
+            ("1 << 2 >> 3", "(1 << 2) >> 3"),
+            // Each level binds tighter than the one before.
+            ("1+2*3^2", "1+(2*(3^2))"),
+            ("-2*3", "(-2)*3"),
+            ("2x^2", "2*(x^2)"),
+            ("30°*2", "(30°)*2"),
+            (
+                "1 | 2 xor 3 & 4 << 1 + 1",
+                "1 | (2 xor (3 & (4 << (1 + 1))))",
+            ),
+            ("1..2 + 3", "1..(2 + 3)"),
+            ("1 < 2 | 3", "1 < (2 | 3)"),
+            ("not 1 < 2 and 3 == 3", "(not (1 < 2)) and (3 == 3)"),
+            ("true or false and false", "true or (false and false)"),
+            ("x or y ? 1 : 2", "(x or y) ? 1 : 2"),
+            ("x ? 1 : y ? 2 : 3", "x ? 1 : (y ? 2 : 3)"),
+        ];
+        for (expr, expected) in rules {
+            assert_eq!(parse(expr), parse(expected), "{}", expr);
+        }
+    }
+    #[test]
+    fn test_custom_operators() {
+        use crate::calcparse::operators::{Associativity, OperatorTable};
+
+        let operators = OperatorTable::default()
+            .with(
+                Token::Caret,
+                Fixity::Infix,
+                OperPrec::Power,
+                Associativity::Left,
+            )
+            .with(
+                Token::Subtract,
+                Fixity::Prefix,
+                OperPrec::Functional,
+                Associativity::Right,
+            );
+        let parse = |expr| {
+            Parser::new(expr, None)
+                .unwrap()
+                .with_operators(operators.clone())
+                .parse()
+                .unwrap()
+        };
+        assert_eq!(parse("2^3^2"), parse("(2^3)^2"));
+        assert_eq!(parse("-2^2"), parse("(-2)^2"));
+        let parser = Parser::new("1", None).unwrap();
+        assert_eq!(parser.operators(), &OperatorTable::default());
+    }
+    #[test]
+    fn test_custom_max_depth() {
+        let mut parser = Parser::new("((1))", None).unwrap().with_max_depth(2);
+        assert_eq!(parser.parse(), Err(ParseError::TooDeeplyNested(2)));
+        let mut parser = Parser::new("((1))", None).unwrap().with_max_depth(3);
+        assert_eq!(parser.parse(), Ok(Number(1.0)));
+    }
+    #[test]
+    fn test_max_allowed_depth() {
+        use crate::calcparse::ast::eval;
+
+        let expr = format!("{}1{}", "(".repeat(100_000), ")".repeat(100_000));
+        let mut parser = Parser::new(&expr, None).unwrap().with_max_depth(10_000_000);
+        assert_eq!(
+            parser.parse(),
+            Err(ParseError::TooDeeplyNested(MAX_ALLOWED_DEPTH))
+        );
+        // Every kind of nesting has to fit in the stack of a test thread up to the limit.
+        let n = MAX_ALLOWED_DEPTH - 1;
+        for (open, close) in [
+            ("(", ")"),
+            ("-", ""),
+            ("2^", ""),
+            ("|", "|"),
+            ("[", "]"),
+            ("sin(", ")"),
+            ("atan2(1, ", ")"),
+            ("max(", ")"),
+            ("x ? ", " : 1"),
+        ] {
+            let expr = format!("{}1{}", open.repeat(n), close.repeat(n));
+            let mut parser = Parser::new(&expr, None)
+                .unwrap()
+                .with_max_depth(MAX_ALLOWED_DEPTH);
+            let ast = parser.parse().unwrap();
+            assert!(!format!("{:?}", ast).is_empty());
+            let _ = eval(ast);
+        }
+    }
+    #[test]
+    fn test_long_negation_chain() {
+        let expr = format!("{}1", "-".repeat(100_000));
+        let mut parser = Parser::new(&expr, None).unwrap();
+        assert_eq!(
+            parser.parse(),
+            Err(ParseError::TooDeeplyNested(MAX_NESTING_DEPTH))
+        );
+    }
+
+    const FRAGMENTS: &[&str] = &[
+        "1",
+        "2.5",
+        ".5",
+        "1e3",
+        "1.",
+        ".",
+        "..",
+        "e",
+        "pi",
+        "π",
+        "@",
+        "+",
+        "-",
+        "*",
+        "/",
+        "\\",
+        "//",
+        "%",
+        "^",
+        "²",
+        "³",
+        "!",
+        "!!",
+        "°",
+        "rad",
+        "(",
+        ")",
+        "[",
+        "]",
+        "|",
+        "⌊",
+        "⌋",
+        "⌈",
+        "⌉",
+        ",",
+        "&",
+        "xor",
+        "~",
+        "<<",
+        ">>",
+        "<",
+        "==",
+        "and",
+        "not",
+        "true",
+        "?",
+        ":",
+        " ",
+        "x",
+        "sin(",
+        "atan2(",
+        "log(",
+        "min(",
+        "max(",
+        "if(",
+        "piecewise(",
+        "sum(x,",
+        "integrate(",
+        "$",
+    ];
+
+    proptest! {
+        #[test]
+        fn parse_never_panics_on_random_tokens(
+            fragments in prop::collection::vec(prop::sample::select(FRAGMENTS), 0..64)
+        ) {
+            let expr = fragments.concat();
+            if let Ok(mut parser) = Parser::new(&expr, None) {
+                let _ = parser.parse();
+            }
+        }
+        #[test]
+        fn recovery_reports_every_failure(
+            fragments in prop::collection::vec(prop::sample::select(FRAGMENTS), 0..64)
+        ) {
+            let expr = fragments.concat();
+            let (_, errors) = Parser::recovering(&expr, None).parse_with_diagnostics();
+            let parsed = Parser::new(&expr, None).and_then(|mut parser| parser.parse());
+            prop_assert!(parsed.is_ok() || !errors.is_empty());
+        }
+        #[test]
+        fn parse_never_panics_on_random_text(expr in "\\PC*") {
+            if let Ok(mut parser) = Parser::new(&expr, None) {
+                let _ = parser.parse();
+            }
+        }
+        #[test]
+        fn parse_never_overflows_on_nesting(
+            open in prop::sample::select(&["(", "|", "⌊", "⌈", "-", "sin(", "2(", "~"][..]),
+            depth in 0usize..5000,
+        ) {
+            let expr = format!("{}1", open.repeat(depth));
+            if let Ok(mut parser) = Parser::new(&expr, None) {
+                let _ = parser.parse();
+            }
+        }
+    }
 }
@@ -0,0 +1,342 @@
+use super::ast::Node;
+use super::error::EvalError;
+use num_complex::Complex64;
+
+/// Highest degree `coefficients` accepts.
+pub const MAX_DEGREE: usize = 64;
+/// Deepest expression `coefficients` walks through.
+const MAX_POLYNOMIAL_DEPTH: usize = 256;
+/// Most iterations of the Durand–Kerner method.
+const MAX_ITERATIONS: usize = 1000;
+
+type Evaluate<'a> = dyn FnMut(&Node) -> Result<f64, EvalError> + 'a;
+
+/// Coefficients of `node` seen as a polynomial in `variable`, lowest degree first.
+/// Sub-expressions that don't depend on `variable` are computed with `eval`.
+pub fn coefficients(
+    node: &Node,
+    variable: &str,
+    eval: &mut Evaluate,
+) -> Result<Vec<f64>, EvalError> {
+    let mut coefficients = read(node, variable, eval, 0)?;
+    while coefficients.len() > 1 && coefficients.last() == Some(&0.0) {
+        coefficients.pop();
+    }
+    Ok(coefficients)
+}
+
+fn read(
+    node: &Node,
+    variable: &str,
+    eval: &mut Evaluate,
+    depth: usize,
+) -> Result<Vec<f64>, EvalError> {
+    use self::Node::*;
+    if depth > MAX_POLYNOMIAL_DEPTH {
+        return Err(EvalError::RecursionLimit(MAX_POLYNOMIAL_DEPTH));
+    }
+    if !node.depends_on(variable) {
+        return Ok(vec![eval(node)?]);
+    }
+    let mut read = |node: &Node| read(node, variable, eval, depth + 1);
+    let coefficients = match node {
+        Variable(_) => vec![0.0, 1.0],
+        Add(u, v) => add(&read(u)?, &read(v)?, 1.0),
+        Subtract(u, v) => add(&read(u)?, &read(v)?, -1.0),
+        Negative(u) => scale(read(u)?, -1.0),
+        Multiply(u, v) => multiply(&read(u)?, &read(v)?)?,
+        Divide(u, v) if !v.depends_on(variable) => {
+            let divisor = read(v)?[0];
+            scale(read(u)?, 1.0 / divisor)
+        }
+        Pow2(u) => power(&read(u)?, 2)?,
+        Pow3(u) => power(&read(u)?, 3)?,
+        Caret(u, v) | Pow(u, v) if !v.depends_on(variable) => {
+            let exponent = read(v)?[0];
+            if exponent < 0.0 || exponent.fract() != 0.0 || exponent > MAX_DEGREE as f64 {
+                return Err(not_polynomial(variable));
+            }
+            power(&read(u)?, exponent as usize)?
+        }
+        _ => return Err(not_polynomial(variable)),
+    };
+    Ok(coefficients)
+}
+
+fn not_polynomial(variable: &str) -> EvalError {
+    EvalError::NotPolynomial(variable.to_string())
+}
+
+fn add(p: &[f64], q: &[f64], sign: f64) -> Vec<f64> {
+    (0..p.len().max(q.len()))
+        .map(|i| p.get(i).unwrap_or(&0.0) + sign * q.get(i).unwrap_or(&0.0))
+        .collect()
+}
+
+fn scale(p: Vec<f64>, factor: f64) -> Vec<f64> {
+    p.into_iter().map(|c| c * factor).collect()
+}
+
+fn multiply(p: &[f64], q: &[f64]) -> Result<Vec<f64>, EvalError> {
+    if p.len() + q.len() > MAX_DEGREE + 2 {
+        return Err(EvalError::TooLarge {
+            what: "degree of a polynomial",
+            limit: MAX_DEGREE,
+        });
+    }
+    let mut product = vec![0.0; p.len() + q.len() - 1];
+    for (i, a) in p.iter().enumerate() {
+        for (j, b) in q.iter().enumerate() {
+            product[i + j] += a * b;
+        }
+    }
+    Ok(product)
+}
+
+fn power(p: &[f64], exponent: usize) -> Result<Vec<f64>, EvalError> {
+    let mut result = vec![1.0];
+    for _ in 0..exponent {
+        result = multiply(&result, p)?;
+    }
+    Ok(result)
+}
+
+/// Every root of the polynomial with the given coefficients, lowest degree first, repeated
+/// according to their multiplicity. Closed forms are used up to the fourth degree, the
+/// Durand–Kerner method beyond. Roots are polished with a few Newton steps afterwards.
+pub fn roots(coefficients: &[f64]) -> Result<Vec<Complex64>, EvalError> {
+    if coefficients.iter().all(|&c| c == 0.0) || coefficients.iter().any(|c| !c.is_finite()) {
+        return Err(EvalError::Domain {
+            function: "roots",
+            arguments: coefficients.to_vec(),
+        });
+    }
+    // Roots at zero are taken out first, they are known exactly.
+    let zeros = coefficients.iter().take_while(|&&c| c == 0.0).count();
+    let mut roots = vec![Complex64::new(0.0, 0.0); zeros];
+    let p: Vec<Complex64> = coefficients[zeros..]
+        .iter()
+        .map(|&c| Complex64::new(c, 0.0))
+        .collect();
+    let found = match p.len() - 1 {
+        0 => Vec::new(),
+        1 => vec![-p[0] / p[1]],
+        2 => quadratic(p[2], p[1], p[0]).to_vec(),
+        3 => cubic(p[3], p[2], p[1], p[0]).to_vec(),
+        4 => quartic(p[4], p[3], p[2], p[1], p[0]).to_vec(),
+        _ => durand_kerner(&p)?,
+    };
+    roots.extend(found.into_iter().map(|root| polish(&p, root)));
+    Ok(roots)
+}
+
+fn quadratic(a: Complex64, b: Complex64, c: Complex64) -> [Complex64; 2] {
+    let discriminant = (b * b - 4.0 * a * c).sqrt();
+    // Pick the sign avoiding cancellation, the other root following from their product.
+    let q = if (b.conj() * discriminant).re >= 0.0 {
+        -0.5 * (b + discriminant)
+    } else {
+        -0.5 * (b - discriminant)
+    };
+    if q == Complex64::new(0.0, 0.0) {
+        [q, q]
+    } else {
+        [q / a, c / q]
+    }
+}
+
+fn cubic(a: Complex64, b: Complex64, c: Complex64, d: Complex64) -> [Complex64; 3] {
+    // x = t - b / 3a gives the depressed cubic t^3 + pt + q.
+    let (b, c, d) = (b / a, c / a, d / a);
+    let shift = b / 3.0;
+    let p = c - b * b / 3.0;
+    let q = 2.0 * b * b * b / 27.0 - b * c / 3.0 + d;
+    let discriminant = (q * q / 4.0 + p * p * p / 27.0).sqrt();
+    let mut u3 = -q / 2.0 + discriminant;
+    if u3.norm() < (-q / 2.0 - discriminant).norm() {
+        u3 = -q / 2.0 - discriminant;
+    }
+    let rotation = Complex64::new(-0.5, 3f64.sqrt() / 2.0);
+    let mut roots = [Complex64::new(0.0, 0.0); 3];
+    let mut u = u3.cbrt();
+    for root in roots.iter_mut() {
+        *root = if u.norm() == 0.0 {
+            Complex64::new(0.0, 0.0)
+        } else {
+            u - p / (3.0 * u)
+        } - shift;
+        u *= rotation;
+    }
+    roots
+}
+
+fn quartic(a: Complex64, b: Complex64, c: Complex64, d: Complex64, e: Complex64) -> [Complex64; 4] {
+    // x = y - b / 4a gives the depressed quartic y^4 + py^2 + qy + r.
+    let (b, c, d, e) = (b / a, c / a, d / a, e / a);
+    let shift = b / 4.0;
+    let p = c - 3.0 * b * b / 8.0;
+    let q = d - b * c / 2.0 + b * b * b / 8.0;
+    let r = e - b * d / 4.0 + b * b * c / 16.0 - 3.0 * b * b * b * b / 256.0;
+    let one = Complex64::new(1.0, 0.0);
+    let roots = if q.norm() <= 1e-14 * (p.norm() + r.norm()).max(1.0) {
+        // Biquadratic, y^2 solves a quadratic.
+        let [z1, z2] = quadratic(one, p, r);
+        [z1.sqrt(), -z1.sqrt(), z2.sqrt(), -z2.sqrt()]
+    } else {
+        // Ferrari: with m a root of 8m^3 + 8pm^2 + (2p^2 - 8r)m - q^2, the quartic is
+        // (y^2 + p/2 + m)^2 - (sqrt(2m) y - q / (2 sqrt(2m)))^2.
+        let m = cubic(8.0 * one, 8.0 * p, 2.0 * p * p - 8.0 * r, -q * q)
+            .into_iter()
+            .max_by(|m1, m2| m1.norm().total_cmp(&m2.norm()))
+            .unwrap_or(one);
+        let s = (2.0 * m).sqrt();
+        let [y1, y2] = quadratic(one, s, p / 2.0 + m - q / (2.0 * s));
+        let [y3, y4] = quadratic(one, -s, p / 2.0 + m + q / (2.0 * s));
+        [y1, y2, y3, y4]
+    };
+    roots.map(|y| y - shift)
+}
+
+fn durand_kerner(p: &[Complex64]) -> Result<Vec<Complex64>, EvalError> {
+    let degree = p.len() - 1;
+    let monic: Vec<Complex64> = p.iter().map(|c| c / p[degree]).collect();
+    // Every root lies within this radius (Cauchy's bound).
+    let radius = 1.0 + monic[..degree].iter().map(|c| c.norm()).fold(0.0, f64::max);
+    let seed = Complex64::new(0.4, 0.9);
+    let mut roots: Vec<Complex64> = (0..degree)
+        .map(|k| radius * seed.powu(k as u32 + 1) / seed.norm().powi(k as i32 + 1))
+        .collect();
+    for _ in 0..MAX_ITERATIONS {
+        let mut change: f64 = 0.0;
+        for i in 0..degree {
+            let mut denominator = Complex64::new(1.0, 0.0);
+            for (j, root) in roots.iter().enumerate() {
+                if i != j {
+                    denominator *= roots[i] - root;
+                }
+            }
+            let step = evaluate(&monic, roots[i]) / denominator;
+            roots[i] -= step;
+            change = change.max(step.norm() / roots[i].norm().max(1.0));
+        }
+        if change <= 1e-12 {
+            return Ok(roots);
+        }
+    }
+    // Multiple roots slow the method down to a linear convergence, what has been found is
+    // still kept as long as it's a root up to rounding errors.
+    let converged = roots.iter().all(|&root| {
+        let scale: f64 = monic
+            .iter()
+            .rev()
+            .fold(0.0, |scale, c| scale * root.norm() + c.norm());
+        evaluate(&monic, root).norm() <= 1e-10 * scale
+    });
+    if converged {
+        Ok(roots)
+    } else {
+        Err(EvalError::NoConvergence(
+            "Finding the roots of the polynomial".to_string(),
+        ))
+    }
+}
+
+/// Horner's scheme, returning the value of the polynomial and of its derivative.
+fn evaluate_with_derivative(p: &[Complex64], x: Complex64) -> (Complex64, Complex64) {
+    let mut value = Complex64::new(0.0, 0.0);
+    let mut derivative = Complex64::new(0.0, 0.0);
+    for c in p.iter().rev() {
+        derivative = derivative * x + value;
+        value = value * x + c;
+    }
+    (value, derivative)
+}
+
+fn evaluate(p: &[Complex64], x: Complex64) -> Complex64 {
+    evaluate_with_derivative(p, x).0
+}
+
+/// Newton steps on the original polynomial, kept only while they reduce the residual.
+fn polish(p: &[Complex64], mut root: Complex64) -> Complex64 {
+    for _ in 0..4 {
+        let (value, derivative) = evaluate_with_derivative(p, root);
+        let next = root - value / derivative;
+        if !next.is_finite() || evaluate(p, next).norm() >= value.norm() {
+            break;
+        }
+        root = next;
+    }
+    root
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Real parts of the roots, sorted, checking that they are real.
+    fn real_roots(coefficients: &[f64]) -> Vec<f64> {
+        let mut roots: Vec<f64> = roots(coefficients)
+            .unwrap()
+            .into_iter()
+            .map(|root| {
+                assert!(root.im.abs() < 1e-9, "{} isn't real", root);
+                root.re
+            })
+            .collect();
+        roots.sort_by(f64::total_cmp);
+        roots
+    }
+    fn assert_close(actual: &[f64], expected: &[f64]) {
+        assert_eq!(actual.len(), expected.len());
+        for (a, e) in actual.iter().zip(expected) {
+            assert!((a - e).abs() < 1e-9, "{:?} != {:?}", actual, expected);
+        }
+    }
+
+    #[test]
+    fn test_closed_forms() {
+        assert_close(&real_roots(&[-6.0, 11.0, -6.0, 1.0]), &[1.0, 2.0, 3.0]);
+        assert_close(
+            &real_roots(&[24.0, -50.0, 35.0, -10.0, 1.0]),
+            &[1.0, 2.0, 3.0, 4.0],
+        );
+        assert_close(&real_roots(&[0.0, 0.0, -1.0, 1.0]), &[0.0, 0.0, 1.0]);
+        assert_close(
+            &real_roots(&[4.0, 0.0, -5.0, 0.0, 1.0]),
+            &[-2.0, -1.0, 1.0, 2.0],
+        );
+    }
+    #[test]
+    fn test_complex_roots() {
+        let roots = roots(&[1.0, 0.0, 1.0]).unwrap();
+        assert!(roots
+            .iter()
+            .all(|root| (root.norm() - 1.0).abs() < 1e-12 && root.re.abs() < 1e-12));
+        let roots = super::roots(&[1.0, 0.0, 0.0, 0.0, 1.0]).unwrap();
+        for root in roots {
+            assert!((root.powu(4) + 1.0).norm() < 1e-12);
+        }
+    }
+    #[test]
+    fn test_durand_kerner() {
+        // (x - 1)(x - 2)(x - 3)(x - 4)(x - 5)
+        let coefficients = [-120.0, 274.0, -225.0, 85.0, -15.0, 1.0];
+        assert_close(&real_roots(&coefficients), &[1.0, 2.0, 3.0, 4.0, 5.0]);
+        let roots = roots(&[-1.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 1.0]).unwrap();
+        assert_eq!(roots.len(), 7);
+        for root in roots {
+            assert!((root.powu(7) - 1.0).norm() < 1e-12);
+        }
+    }
+    #[test]
+    fn test_multiple_roots() {
+        // (x - 1)^6, only known up to about the sixth root of the machine epsilon.
+        let roots = roots(&[1.0, -6.0, 15.0, -20.0, 15.0, -6.0, 1.0]).unwrap();
+        assert!(roots.iter().all(|root| (root - 1.0).norm() < 1e-2));
+    }
+    #[test]
+    fn test_degenerate() {
+        assert!(roots(&[0.0]).is_err());
+        assert!(roots(&[3.0]).unwrap().is_empty());
+    }
+}
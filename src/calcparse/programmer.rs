@@ -0,0 +1,229 @@
+use super::error::EvalError;
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Overflow {
+    Wrapping,
+    Checked,
+}
+
+/// Fixed width integer semantics used by the bitwise operators and by programmer mode.
+///
+/// Values are carried as `f64` by the evaluator, so integers wider than 53 bits
+/// are only exact as long as they are representable by a double.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct IntegerMode {
+    bits: u32,
+    signed: bool,
+    overflow: Overflow,
+}
+
+impl Default for IntegerMode {
+    fn default() -> Self {
+        IntegerMode {
+            bits: 64,
+            signed: true,
+            overflow: Overflow::Checked,
+        }
+    }
+}
+
+impl IntegerMode {
+    pub fn new(bits: u32, signed: bool, overflow: Overflow) -> Option<Self> {
+        match bits {
+            8 | 16 | 32 | 64 => Some(IntegerMode {
+                bits,
+                signed,
+                overflow,
+            }),
+            _ => None,
+        }
+    }
+    /// Parse a width such as `i8`, `u16`, `i32` or `u64`.
+    pub fn from_width(width: &str, overflow: Overflow) -> Option<Self> {
+        let signed = match width.chars().next()? {
+            'i' => true,
+            'u' => false,
+            _ => return None,
+        };
+        IntegerMode::new(width[1..].parse().ok()?, signed, overflow)
+    }
+    pub fn min(&self) -> i128 {
+        if self.signed {
+            -(1 << (self.bits - 1))
+        } else {
+            0
+        }
+    }
+    pub fn max(&self) -> i128 {
+        if self.signed {
+            (1 << (self.bits - 1)) - 1
+        } else {
+            (1 << self.bits) - 1
+        }
+    }
+    pub fn integer_from(&self, value: f64) -> Result<i128, EvalError> {
+        if !value.is_finite() || value.fract() != 0.0 {
+            return Err(EvalError::TypeMismatch {
+                expected: "an integer",
+                got: value.to_string(),
+            });
+        }
+        // Anything beyond 2^127 cannot be represented, it's an overflow in every width.
+        if value.abs() >= 2f64.powi(127) {
+            return self.fit(None, 0);
+        }
+        let value = value as i128;
+        self.fit(Some(value), value)
+    }
+    /// Bring a result back into range, `exact` being `None` when the operation itself overflowed.
+    fn fit(&self, exact: Option<i128>, wrapped: i128) -> Result<i128, EvalError> {
+        match exact {
+            Some(value) if (self.min()..=self.max()).contains(&value) => Ok(value),
+            _ => match self.overflow {
+                Overflow::Checked => Err(EvalError::IntegerOverflow(*self)),
+                Overflow::Wrapping => Ok(self.wrap(wrapped)),
+            },
+        }
+    }
+    fn wrap(&self, value: i128) -> i128 {
+        let modulus = 1i128 << self.bits;
+        let value = value.rem_euclid(modulus);
+        if self.signed && value > self.max() {
+            value - modulus
+        } else {
+            value
+        }
+    }
+    pub fn add(&self, a: i128, b: i128) -> Result<i128, EvalError> {
+        self.fit(a.checked_add(b), a.wrapping_add(b))
+    }
+    pub fn sub(&self, a: i128, b: i128) -> Result<i128, EvalError> {
+        self.fit(a.checked_sub(b), a.wrapping_sub(b))
+    }
+    pub fn mul(&self, a: i128, b: i128) -> Result<i128, EvalError> {
+        self.fit(a.checked_mul(b), a.wrapping_mul(b))
+    }
+    pub fn neg(&self, a: i128) -> Result<i128, EvalError> {
+        self.fit(a.checked_neg(), a.wrapping_neg())
+    }
+    /// Division truncated toward zero, like in most programming languages.
+    pub fn div(&self, a: i128, b: i128) -> Result<i128, EvalError> {
+        if b == 0 {
+            return Err(division_by_zero("division", a));
+        }
+        self.fit(a.checked_div(b), a.wrapping_div(b))
+    }
+    /// Division rounded toward negative infinity.
+    pub fn floor_div(&self, a: i128, b: i128) -> Result<i128, EvalError> {
+        if b == 0 {
+            return Err(division_by_zero("integer division", a));
+        }
+        let quotient = a / b;
+        if (a % b != 0) && ((a < 0) != (b < 0)) {
+            self.fit(Some(quotient - 1), quotient - 1)
+        } else {
+            self.fit(Some(quotient), quotient)
+        }
+    }
+    pub fn rem(&self, a: i128, b: i128) -> Result<i128, EvalError> {
+        if b == 0 {
+            return Err(division_by_zero("modulo", a));
+        }
+        self.fit(Some(a % b), a % b)
+    }
+    pub fn and(&self, a: i128, b: i128) -> Result<i128, EvalError> {
+        Ok(self.wrap(a & b))
+    }
+    pub fn or(&self, a: i128, b: i128) -> Result<i128, EvalError> {
+        Ok(self.wrap(a | b))
+    }
+    pub fn xor(&self, a: i128, b: i128) -> Result<i128, EvalError> {
+        Ok(self.wrap(a ^ b))
+    }
+    pub fn not(&self, a: i128) -> Result<i128, EvalError> {
+        Ok(self.wrap(!a))
+    }
+    pub fn shl(&self, a: i128, shift: i128) -> Result<i128, EvalError> {
+        if shift < 0 {
+            return Err(negative_shift("shift left", a, shift));
+        }
+        if shift >= self.bits as i128 {
+            return self.fit((a == 0).then_some(0), 0);
+        }
+        self.fit(a.checked_mul(1 << shift), a.wrapping_shl(shift as u32))
+    }
+    /// Arithmetic shift for signed widths, logical shift for unsigned ones.
+    pub fn shr(&self, a: i128, shift: i128) -> Result<i128, EvalError> {
+        if shift < 0 {
+            return Err(negative_shift("shift right", a, shift));
+        }
+        Ok(a >> shift.min(127))
+    }
+}
+
+fn division_by_zero(function: &'static str, a: i128) -> EvalError {
+    EvalError::DivisionByZero {
+        function,
+        arguments: vec![a as f64, 0.0],
+    }
+}
+
+fn negative_shift(function: &'static str, a: i128, shift: i128) -> EvalError {
+    EvalError::Domain {
+        function,
+        arguments: vec![a as f64, shift as f64],
+    }
+}
+
+impl fmt::Display for IntegerMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let overflow = match self.overflow {
+            Overflow::Wrapping => "wrapping",
+            Overflow::Checked => "checked",
+        };
+        let sign = if self.signed { 'i' } else { 'u' };
+        write!(f, "{}{} ({})", sign, self.bits, overflow)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wrapping_u8() {
+        let mode = IntegerMode::from_width("u8", Overflow::Wrapping).unwrap();
+        assert_eq!(mode.add(250, 10).unwrap(), 4);
+        assert_eq!(mode.sub(0, 1).unwrap(), 255);
+        assert_eq!(mode.not(0).unwrap(), 255);
+        assert_eq!(mode.shl(1, 8).unwrap(), 0);
+    }
+    #[test]
+    fn test_checked_i8() {
+        let mode = IntegerMode::from_width("i8", Overflow::Checked).unwrap();
+        assert_eq!(mode.add(100, 27).unwrap(), 127);
+        assert!(mode.add(100, 28).is_err());
+        assert!(mode.neg(-128).is_err());
+        assert!(mode.integer_from(300.0).is_err());
+        assert_eq!(mode.shr(-8, 1).unwrap(), -4);
+    }
+    #[test]
+    fn test_wrapping_u64_multiply() {
+        let mode = IntegerMode::from_width("u64", Overflow::Wrapping).unwrap();
+        let max = mode.max();
+        assert_eq!(mode.mul(max, max).unwrap(), 1);
+    }
+    #[test]
+    fn test_floor_division() {
+        let mode = IntegerMode::default();
+        assert_eq!(mode.floor_div(-7, 2).unwrap(), -4);
+        assert_eq!(mode.div(-7, 2).unwrap(), -3);
+        assert!(mode.div(1, 0).is_err());
+    }
+    #[test]
+    fn test_invalid_width() {
+        assert_eq!(IntegerMode::from_width("i12", Overflow::Checked), None);
+        assert_eq!(IntegerMode::from_width("x32", Overflow::Checked), None);
+    }
+}
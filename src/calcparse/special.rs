@@ -0,0 +1,315 @@
+use super::error::EvalError;
+use super::numeric;
+use statrs::function::{beta, gamma};
+use std::f64::consts::{E, LN_2, PI};
+
+/// Relative precision the integral representations of Bessel functions are computed to.
+const BESSEL_PRECISION: f64 = 1e-14;
+/// Number of terms of Borwein's acceleration of the eta function, enough for an error below
+/// `f64::EPSILON` on the real line.
+const ZETA_TERMS: usize = 50;
+/// Most Halley steps taken by `lambert_w`.
+const MAX_ITERATIONS: usize = 100;
+
+/// Whether `x` is one of the poles of the gamma function, `0, -1, -2, ...`.
+fn is_gamma_pole(x: f64) -> bool {
+    x <= 0.0 && x.fract() == 0.0
+}
+
+/// The gamma function. statrs gives finite values at its poles, so they are handled here like
+/// C's `tgamma`: an infinity with the sign of a zero, and NaN at negative integers, where the
+/// limits from both sides have opposite signs.
+pub fn gamma(x: f64) -> f64 {
+    if x == 0.0 {
+        1.0 / x
+    } else if is_gamma_pole(x) {
+        f64::NAN
+    } else {
+        gamma::gamma(x)
+    }
+}
+
+/// Logarithm of the absolute value of the gamma function.
+pub fn ln_gamma(x: f64) -> f64 {
+    if is_gamma_pole(x) {
+        f64::INFINITY
+    } else if x < 0.5 {
+        // Reflection formula, as Γ(x) Γ(1 - x) = π / sin(πx).
+        PI.ln() - (PI * x).sin().abs().ln() - gamma::ln_gamma(1.0 - x)
+    } else {
+        gamma::ln_gamma(x)
+    }
+}
+
+/// The beta function, only defined here for positive arguments.
+pub fn beta(a: f64, b: f64) -> f64 {
+    beta::checked_beta(a, b).unwrap_or(f64::NAN)
+}
+
+/// Regularized lower incomplete gamma function `P(a, x)`.
+pub fn gamma_inc(a: f64, x: f64) -> f64 {
+    gamma::checked_gamma_lr(a, x).unwrap_or(f64::NAN)
+}
+
+/// Regularized upper incomplete gamma function `Q(a, x) = 1 - P(a, x)`.
+pub fn gamma_inc_complement(a: f64, x: f64) -> f64 {
+    gamma::checked_gamma_ur(a, x).unwrap_or(f64::NAN)
+}
+
+/// Regularized incomplete beta function `I_x(a, b)`.
+pub fn beta_inc(a: f64, b: f64, x: f64) -> f64 {
+    beta::checked_beta_reg(a, b, x).unwrap_or(f64::NAN)
+}
+
+fn integral(f: &mut dyn FnMut(f64) -> f64, a: f64, b: f64) -> Result<f64, EvalError> {
+    Ok(numeric::integrate(&mut |t| Ok(f(t)), a, b, BESSEL_PRECISION)?.value)
+}
+
+/// Value of a Bessel function of non integer `order` at zero, from the leading term of its
+/// series.
+fn bessel_at_zero(order: f64) -> f64 {
+    if order > 0.0 {
+        0.0
+    } else {
+        f64::NAN
+    }
+}
+
+/// Bessel function of the first kind, from Bessel's integral with Schläfli's correction for non
+/// integer orders.
+pub fn bessel_j(order: f64, x: f64) -> Result<f64, EvalError> {
+    let main = |x: f64| integral(&mut |t| (order * t - x * t.sin()).cos(), 0.0, PI);
+    if order.fract() == 0.0 {
+        return Ok(main(x)? / PI);
+    }
+    if x < 0.0 || x.is_nan() {
+        return Ok(f64::NAN);
+    }
+    if x == 0.0 {
+        return Ok(bessel_at_zero(order));
+    }
+    let correction = integral(
+        &mut |t| (-x * t.sinh() - order * t).exp(),
+        0.0,
+        f64::INFINITY,
+    )?;
+    Ok((main(x)? - (order * PI).sin() * correction) / PI)
+}
+
+/// Bessel function of the second kind, only defined for positive `x`.
+pub fn bessel_y(order: f64, x: f64) -> Result<f64, EvalError> {
+    if x <= 0.0 || x.is_nan() {
+        return Ok(if x == 0.0 {
+            f64::NEG_INFINITY
+        } else {
+            f64::NAN
+        });
+    }
+    let main = integral(&mut |t| (x * t.sin() - order * t).sin(), 0.0, PI)?;
+    let correction = integral(
+        &mut |t| {
+            (order * t - x * t.sinh()).exp()
+                + (order * PI).cos() * (-order * t - x * t.sinh()).exp()
+        },
+        0.0,
+        f64::INFINITY,
+    )?;
+    Ok((main - correction) / PI)
+}
+
+/// Modified Bessel function of the first kind.
+pub fn bessel_i(order: f64, x: f64) -> Result<f64, EvalError> {
+    let main = |x: f64| integral(&mut |t| (x * t.cos()).exp() * (order * t).cos(), 0.0, PI);
+    if order.fract() == 0.0 {
+        return Ok(main(x)? / PI);
+    }
+    if x < 0.0 || x.is_nan() {
+        return Ok(f64::NAN);
+    }
+    if x == 0.0 {
+        return Ok(bessel_at_zero(order));
+    }
+    let correction = integral(
+        &mut |t| (-x * t.cosh() - order * t).exp(),
+        0.0,
+        f64::INFINITY,
+    )?;
+    Ok((main(x)? - (order * PI).sin() * correction) / PI)
+}
+
+/// Modified Bessel function of the second kind, only defined for positive `x`.
+pub fn bessel_k(order: f64, x: f64) -> Result<f64, EvalError> {
+    if x <= 0.0 || x.is_nan() {
+        return Ok(if x == 0.0 { f64::INFINITY } else { f64::NAN });
+    }
+    integral(
+        &mut |t| 0.5 * ((order * t - x * t.cosh()).exp() + (-order * t - x * t.cosh()).exp()),
+        0.0,
+        f64::INFINITY,
+    )
+}
+
+/// Dirichlet eta function for `s >= 0`, with Borwein's algorithm 2.
+fn eta(s: f64) -> f64 {
+    let n = ZETA_TERMS;
+    let mut d = Vec::with_capacity(n + 1);
+    let mut term = 1.0 / n as f64;
+    let mut sum = term;
+    d.push(n as f64 * sum);
+    for i in 1..=n {
+        term *= 4.0 * (n + i - 1) as f64 * (n - i + 1) as f64 / ((2 * i) * (2 * i - 1)) as f64;
+        sum += term;
+        d.push(n as f64 * sum);
+    }
+    let total: f64 = (0..n)
+        .map(|k| {
+            let sign = if k % 2 == 0 { 1.0 } else { -1.0 };
+            sign * (d[k] - d[n]) / ((k + 1) as f64).powf(s)
+        })
+        .sum();
+    -total / d[n]
+}
+
+/// Riemann zeta function on the real line, with a pole at 1.
+pub fn zeta(s: f64) -> f64 {
+    if s.is_nan() || s == 1.0 {
+        return f64::NAN;
+    }
+    if s == f64::INFINITY {
+        return 1.0;
+    }
+    if s < 0.0 {
+        if s % 2.0 == 0.0 {
+            return 0.0;
+        }
+        // Functional equation, ζ(s) = 2^s π^(s-1) sin(πs/2) Γ(1-s) ζ(1-s), the magnitude of the
+        // product being computed with logarithms since Γ(1-s) overflows long before it.
+        let magnitude = (s * LN_2 + (s - 1.0) * PI.ln() + ln_gamma(1.0 - s)).exp();
+        return magnitude * (0.5 * PI * s).sin() * zeta(1.0 - s);
+    }
+    // ζ(s) = η(s) / (1 - 2^(1-s)).
+    eta(s) / -((1.0 - s) * LN_2).exp_m1()
+}
+
+/// Principal branch of the Lambert W function, the solution of `w e^w = x` with `w >= -1`,
+/// defined for `x >= -1/e`.
+pub fn lambert_w(x: f64) -> f64 {
+    let branch_point = -1.0 / E;
+    if x.is_nan() || x < branch_point {
+        return f64::NAN;
+    }
+    if x == f64::INFINITY {
+        return x;
+    }
+    let mut w = if x < -0.25 {
+        // Series around the branch point.
+        let p = (2.0 * (E * x + 1.0)).max(0.0).sqrt();
+        -1.0 + p - p * p / 3.0 + 11.0 / 72.0 * p * p * p
+    } else if x < 3.0 {
+        let l = x.ln_1p();
+        l * (1.0 - (1.0 + l).ln() / (2.0 + l))
+    } else {
+        let (l1, l2) = (x.ln(), x.ln().ln());
+        l1 - l2 + l2 / l1
+    };
+    for _ in 0..MAX_ITERATIONS {
+        let exp_w = w.exp();
+        let residual = w * exp_w - x;
+        let step = residual / (exp_w * (w + 1.0) - (w + 2.0) * residual / (2.0 * w + 2.0));
+        if !step.is_finite() {
+            break;
+        }
+        w -= step;
+        if step.abs() <= 4.0 * f64::EPSILON * (1.0 + w.abs()) {
+            break;
+        }
+    }
+    w
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: f64, expected: f64, tolerance: f64) {
+        assert!(
+            (actual - expected).abs() <= tolerance * expected.abs().max(1.0),
+            "{} != {}",
+            actual,
+            expected
+        );
+    }
+    #[test]
+    fn test_gamma_family() {
+        assert_close(ln_gamma(-0.5), 1.265_512_123_484_645_4, 1e-14);
+        assert_close(ln_gamma(10.0), 12.801_827_480_081_469, 1e-14);
+        assert_close(gamma(-0.5), -2.0 * PI.sqrt(), 1e-14);
+        assert!(gamma(-1.0).is_nan());
+        assert_eq!(gamma(0.0), f64::INFINITY);
+        assert_eq!(gamma(-0.0), f64::NEG_INFINITY);
+        assert_eq!(ln_gamma(-1.0), f64::INFINITY);
+        assert_eq!(ln_gamma(0.0), f64::INFINITY);
+        assert!(beta(-1.0, 2.0).is_nan());
+        assert_close(beta(2.0, 3.0), 1.0 / 12.0, 1e-14);
+        assert!(beta(-1.0, 3.0).is_nan());
+        assert_close(gamma_inc(1.0, 2.0), 1.0 - (-2f64).exp(), 1e-14);
+        assert_close(gamma_inc_complement(3.0, 2.0), 5.0 * (-2f64).exp(), 1e-14);
+        assert_close(beta_inc(2.0, 2.0, 0.25), 0.156_25, 1e-14);
+        assert!(beta_inc(2.0, 2.0, 1.5).is_nan());
+    }
+    #[test]
+    fn test_bessel_integer_order() {
+        assert_close(bessel_j(0.0, 1.0).unwrap(), 0.765_197_686_557_966_6, 1e-14);
+        assert_close(bessel_j(1.0, 2.5).unwrap(), 0.497_094_102_464_274_1, 1e-14);
+        assert_close(
+            bessel_j(1.0, -2.5).unwrap(),
+            -0.497_094_102_464_274_1,
+            1e-14,
+        );
+        assert_close(
+            bessel_j(2.0, 50.0).unwrap(),
+            -0.059_712_800_794_258_82,
+            1e-12,
+        );
+        assert_close(bessel_y(0.0, 1.0).unwrap(), 0.088_256_964_215_676_96, 1e-13);
+        assert_close(bessel_y(1.0, 3.0).unwrap(), 0.324_674_424_791_799_9, 1e-13);
+        assert_close(bessel_i(1.0, 1.0).unwrap(), 0.565_159_103_992_485, 1e-14);
+        assert_close(bessel_k(0.0, 1.0).unwrap(), 0.421_024_438_240_708_3, 1e-14);
+        assert!(bessel_y(0.0, -1.0).unwrap().is_nan());
+    }
+    #[test]
+    fn test_bessel_half_order() {
+        let x: f64 = 2.0;
+        let scale = (2.0 / (PI * x)).sqrt();
+        assert_close(bessel_j(0.5, x).unwrap(), scale * x.sin(), 1e-13);
+        assert_close(bessel_j(-0.5, x).unwrap(), scale * x.cos(), 1e-13);
+        assert_close(bessel_y(0.5, x).unwrap(), -scale * x.cos(), 1e-13);
+        assert_close(bessel_i(0.5, x).unwrap(), scale * x.sinh(), 1e-13);
+        assert_close(
+            bessel_k(0.5, x).unwrap(),
+            (PI / (2.0 * x)).sqrt() * (-x).exp(),
+            1e-13,
+        );
+    }
+    #[test]
+    fn test_zeta() {
+        assert_close(zeta(2.0), PI * PI / 6.0, 1e-15);
+        assert_close(zeta(3.0), 1.202_056_903_159_594_2, 1e-15);
+        assert_close(zeta(0.5), -1.460_354_508_809_586_8, 1e-14);
+        assert_close(zeta(0.0), -0.5, 1e-15);
+        assert_close(zeta(-1.0), -1.0 / 12.0, 1e-14);
+        assert_close(zeta(-3.5), 0.004_441_011_335_479_432, 1e-12);
+        assert_eq!(zeta(-2.0), 0.0);
+        assert!(zeta(1.0).is_nan());
+    }
+    #[test]
+    fn test_lambert_w() {
+        assert_close(lambert_w(1.0), 0.567_143_290_409_783_8, 1e-15);
+        assert_close(lambert_w(E), 1.0, 1e-15);
+        assert_close(lambert_w(-1.0 / E), -1.0, 1e-7);
+        assert_close(lambert_w(-0.3), -0.489_402_227_180_214_2, 1e-14);
+        assert_close(lambert_w(1e300), 684.247_208_629_760_8, 1e-14);
+        assert_eq!(lambert_w(0.0), 0.0);
+        assert!(lambert_w(-1.0).is_nan());
+    }
+}
@@ -0,0 +1,178 @@
+use super::error::EvalError;
+use statrs::statistics::{Data, OrderStatistics, Statistics};
+
+/// Reject data with less than `count` values.
+fn at_least(data: &[f64], count: usize, function: &'static str) -> Result<(), EvalError> {
+    if data.len() < count {
+        return Err(EvalError::ArityMismatch {
+            function,
+            expected: format!("at least {}", count),
+            got: data.len(),
+        });
+    }
+    Ok(())
+}
+
+/// Reject data with a value that isn't positive.
+fn positive(data: &[f64], function: &'static str) -> Result<(), EvalError> {
+    match data.iter().find(|x| **x <= 0.0) {
+        Some(x) => Err(EvalError::Domain {
+            function,
+            arguments: vec![*x],
+        }),
+        None => Ok(()),
+    }
+}
+
+pub fn mean(data: &[f64]) -> Result<f64, EvalError> {
+    at_least(data, 1, "mean")?;
+    Ok(data.mean())
+}
+
+pub fn median(data: &[f64]) -> Result<f64, EvalError> {
+    at_least(data, 1, "median")?;
+    Ok(Data::new(data.to_vec()).median())
+}
+
+/// The most frequent value, the smallest one in case of a tie.
+pub fn mode(data: &[f64]) -> Result<f64, EvalError> {
+    at_least(data, 1, "mode")?;
+    let mut sorted = data.to_vec();
+    sorted.sort_by(f64::total_cmp);
+    let mut mode = (sorted[0], 0);
+    for run in sorted.chunk_by(|a, b| a == b) {
+        if run.len() > mode.1 {
+            mode = (run[0], run.len());
+        }
+    }
+    Ok(mode.0)
+}
+
+/// Variance of a sample, normalized by `n - 1`, for `function` such as `var` or `stdev`.
+pub fn variance(data: &[f64], function: &'static str) -> Result<f64, EvalError> {
+    at_least(data, 2, function)?;
+    Ok(data.variance())
+}
+
+/// Variance of a whole population, normalized by `n`, for `function` such as `varp` or
+/// `stdevp`.
+pub fn population_variance(data: &[f64], function: &'static str) -> Result<f64, EvalError> {
+    at_least(data, 1, function)?;
+    Ok(data.population_variance())
+}
+
+/// Quantile of order `p`, interpolated as recommended by Hyndman and Fan (definition 8).
+pub fn quantile(data: &[f64], p: f64) -> Result<f64, EvalError> {
+    at_least(data, 1, "quantile")?;
+    if !(0.0..=1.0).contains(&p) {
+        return Err(EvalError::Domain {
+            function: "quantile",
+            arguments: vec![p],
+        });
+    }
+    Ok(Data::new(data.to_vec()).quantile(p))
+}
+
+pub fn geometric_mean(data: &[f64]) -> Result<f64, EvalError> {
+    at_least(data, 1, "geomean")?;
+    positive(data, "geomean")?;
+    Ok(data.geometric_mean())
+}
+
+pub fn harmonic_mean(data: &[f64]) -> Result<f64, EvalError> {
+    at_least(data, 1, "harmean")?;
+    positive(data, "harmean")?;
+    Ok(data.harmonic_mean())
+}
+
+/// Second, third and fourth central moments of the data.
+fn central_moments(data: &[f64], function: &'static str) -> Result<[f64; 3], EvalError> {
+    at_least(data, 2, function)?;
+    let mean = data.mean();
+    let mut moments = [0.0; 3];
+    for x in data {
+        let deviation = x - mean;
+        moments[0] += deviation.powi(2);
+        moments[1] += deviation.powi(3);
+        moments[2] += deviation.powi(4);
+    }
+    let moments = moments.map(|moment| moment / data.len() as f64);
+    if moments[0] == 0.0 {
+        return Err(EvalError::Domain {
+            function,
+            arguments: data.to_vec(),
+        });
+    }
+    Ok(moments)
+}
+
+/// Moment coefficient of skewness, `m3 / m2^(3/2)`.
+pub fn skewness(data: &[f64]) -> Result<f64, EvalError> {
+    let [m2, m3, _] = central_moments(data, "skewness")?;
+    Ok(m3 / m2.powf(1.5))
+}
+
+/// Excess kurtosis, `m4 / m2² - 3`, which is zero for a normal distribution.
+pub fn kurtosis(data: &[f64]) -> Result<f64, EvalError> {
+    let [m2, _, m4] = central_moments(data, "kurtosis")?;
+    Ok(m4 / (m2 * m2) - 3.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DATA: [f64; 8] = [2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0];
+
+    #[test]
+    fn test_location() {
+        assert_eq!(mean(&DATA).unwrap(), 5.0);
+        assert_eq!(median(&DATA).unwrap(), 4.5);
+        assert_eq!(mode(&DATA).unwrap(), 4.0);
+        assert_eq!(mode(&[3.0, 1.0, 3.0, 1.0]).unwrap(), 1.0);
+        assert_eq!(quantile(&DATA, 0.0).unwrap(), 2.0);
+        assert_eq!(quantile(&DATA, 1.0).unwrap(), 9.0);
+        assert!(matches!(
+            quantile(&DATA, 1.5),
+            Err(EvalError::Domain {
+                function: "quantile",
+                ..
+            })
+        ));
+        assert!((geometric_mean(&[1.0, 4.0, 16.0]).unwrap() - 4.0).abs() < 1e-12);
+        assert_eq!(harmonic_mean(&[1.0, 4.0, 4.0]).unwrap(), 2.0);
+        assert_eq!(
+            geometric_mean(&[1.0, -1.0]),
+            Err(EvalError::Domain {
+                function: "geomean",
+                arguments: vec![-1.0],
+            })
+        );
+        assert!(matches!(
+            mean(&[]),
+            Err(EvalError::ArityMismatch { got: 0, .. })
+        ));
+    }
+    #[test]
+    fn test_spread_and_shape() {
+        assert_eq!(population_variance(&DATA, "varp").unwrap(), 4.0);
+        assert_eq!(variance(&DATA, "var").unwrap(), 32.0 / 7.0);
+        assert_eq!(
+            variance(&[1.0], "stdev"),
+            Err(EvalError::ArityMismatch {
+                function: "stdev",
+                expected: "at least 2".to_string(),
+                got: 1,
+            })
+        );
+        assert!((skewness(&DATA).unwrap() - 0.65625).abs() < 1e-12);
+        assert!((kurtosis(&DATA).unwrap() + 0.21875).abs() < 1e-12);
+        assert!(matches!(
+            skewness(&[1.0, 1.0]),
+            Err(EvalError::Domain {
+                function: "skewness",
+                ..
+            })
+        ));
+    }
+}
@@ -0,0 +1,109 @@
+use super::constants::CONSTANTS;
+use super::parser::expected_arguments;
+use super::token::FUNCTIONS;
+use std::fmt;
+
+/// A known name close to one that isn't, as in "did you mean `sqrt`?".
+#[derive(Debug, Clone, PartialEq)]
+pub struct Suggestion {
+    pub name: &'static str,
+    /// What the function expects, as in "1 argument", `None` for a constant.
+    pub arguments: Option<String>,
+}
+
+impl fmt::Display for Suggestion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "did you mean `{}`?", self.name)?;
+        if let Some(arguments) = &self.arguments {
+            write!(f, " It takes {}", arguments)?;
+        }
+        Ok(())
+    }
+}
+
+/// Number of insertions, deletions, substitutions and swaps of adjacent characters turning `a`
+/// into `b`.
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let (a, b) = (a.chars().collect::<Vec<_>>(), b.chars().collect::<Vec<_>>());
+    // Distances from the prefixes of `a` to the prefixes of `b`, two rows before this one first.
+    let mut rows = [Vec::new(), Vec::new(), (0..=b.len()).collect::<Vec<_>>()];
+    for i in 1..=a.len() {
+        rows.rotate_left(1);
+        rows[2] = vec![i; b.len() + 1];
+        for j in 1..=b.len() {
+            let substitution = rows[1][j - 1] + usize::from(a[i - 1] != b[j - 1]);
+            let mut distance = substitution.min(rows[1][j] + 1).min(rows[2][j - 1] + 1);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                distance = distance.min(rows[0][j - 2] + 1);
+            }
+            rows[2][j] = distance;
+        }
+    }
+    rows[2][b.len()]
+}
+
+/// The function or constant whose name is the closest to `name`, if any is close enough for a
+/// typo. Names shorter than 3 characters are left alone, they're usually meant as variables.
+pub fn suggest(name: &str) -> Option<Suggestion> {
+    let length = name.chars().count();
+    if length < 3 {
+        return None;
+    }
+    let max_distance = if length <= 4 { 1 } else { 2 };
+    let functions = FUNCTIONS
+        .iter()
+        .map(|(name, function)| (*name, Some(expected_arguments(function))));
+    let constants = CONSTANTS.iter().map(|constant| (constant.name, None));
+    functions
+        .chain(constants)
+        .map(|(candidate, arguments)| (edit_distance(name, candidate), candidate, arguments))
+        .filter(|(distance, _, _)| *distance <= max_distance)
+        .min_by_key(|(distance, _, _)| *distance)
+        .map(|(_, name, arguments)| Suggestion { name, arguments })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("sqr", "sqrt"), 1);
+        assert_eq!(edit_distance("sine", "sin"), 1);
+        assert_eq!(edit_distance("lgo", "log"), 1);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("", "abc"), 3);
+        assert_eq!(edit_distance("tau", "tau"), 0);
+    }
+    #[test]
+    fn test_suggest() {
+        let suggestion = suggest("sqr").unwrap();
+        assert_eq!(suggestion.name, "sqrt");
+        assert_eq!(
+            suggestion.to_string(),
+            "did you mean `sqrt`? It takes 1 argument"
+        );
+        assert_eq!(suggest("sine").unwrap().name, "sin");
+        assert_eq!(
+            suggest("beselj").unwrap().arguments.as_deref(),
+            Some("2 arguments")
+        );
+        assert_eq!(
+            suggest("integral").unwrap().arguments.as_deref(),
+            Some("4 arguments")
+        );
+        assert_eq!(
+            suggest("mean_").unwrap().to_string(),
+            "did you mean `mean`? It takes at least 1 argument"
+        );
+        assert_eq!(
+            suggest("tua").unwrap(),
+            Suggestion {
+                name: "tau",
+                arguments: None
+            }
+        );
+        assert_eq!(suggest("x"), None);
+        assert_eq!(suggest("velocity"), None);
+    }
+}
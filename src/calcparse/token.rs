@@ -1,3 +1,6 @@
+use super::distribution::{Distribution, DistributionFunction};
+use super::finance::Financial;
+
 #[derive(Debug, PartialEq, Clone)]
 pub enum NativeFunction {
     Sin,
@@ -27,6 +30,315 @@ pub enum NativeFunction {
     Round,
     Min,
     Max,
+    If,
+    Piecewise,
+    Sum,
+    Product,
+    Integrate,
+    Solve,
+    Root,
+    Roots,
+    Derivative,
+    Limit,
+    LimitLeft,
+    LimitRight,
+    Len,
+    Transpose,
+    Determinant,
+    Inverse,
+    Identity,
+    Trace,
+    Rank,
+    Eigenvalues,
+    Mean,
+    Median,
+    Mode,
+    Variance,
+    PopulationVariance,
+    StandardDeviation,
+    PopulationStandardDeviation,
+    Quantile,
+    GeometricMean,
+    HarmonicMean,
+    Skewness,
+    Kurtosis,
+    Gamma,
+    LnGamma,
+    Digamma,
+    Erf,
+    Erfc,
+    ErfInv,
+    Zeta,
+    LambertW,
+    Beta,
+    BesselJ,
+    BesselY,
+    BesselI,
+    BesselK,
+    GammaInc,
+    GammaIncC,
+    BetaInc,
+    Binomial,
+    Permutations,
+    Gcd,
+    Lcm,
+    IsPrime,
+    NextPrime,
+    Factor,
+    Totient,
+    ModPow,
+    ModInv,
+    Fibonacci,
+    Subfactorial,
+    Rand,
+    RandInt,
+    Choice,
+    Distribution(Distribution, DistributionFunction),
+    /// A random number following a distribution.
+    Sample(Distribution),
+    Financial(Financial),
+}
+
+/// Names under which each function can be called.
+pub const FUNCTIONS: &[(&str, NativeFunction)] = &[
+    ("sin", NativeFunction::Sin),
+    ("cos", NativeFunction::Cos),
+    ("tan", NativeFunction::Tan),
+    ("sinh", NativeFunction::Sinh),
+    ("cosh", NativeFunction::Cosh),
+    ("tanh", NativeFunction::Tanh),
+    ("asin", NativeFunction::Asin),
+    ("acos", NativeFunction::Acos),
+    ("atan", NativeFunction::Atan),
+    ("atan2", NativeFunction::Atan2),
+    ("asinh", NativeFunction::Arsinh),
+    ("arsinh", NativeFunction::Arsinh),
+    ("acosh", NativeFunction::Arcosh),
+    ("arcosh", NativeFunction::Arcosh),
+    ("atanh", NativeFunction::Artanh),
+    ("artanh", NativeFunction::Artanh),
+    ("ln", NativeFunction::Ln),
+    ("log", NativeFunction::Log),
+    ("pow", NativeFunction::Pow),
+    ("sqrt", NativeFunction::Sqrt),
+    ("exp", NativeFunction::Exp),
+    ("exp2", NativeFunction::Exp2),
+    ("abs", NativeFunction::Abs),
+    ("sign", NativeFunction::Sign),
+    ("signum", NativeFunction::Sign),
+    ("sgn", NativeFunction::Sign),
+    ("truncate", NativeFunction::Truncate),
+    ("trunc", NativeFunction::Truncate),
+    ("floor", NativeFunction::Floor),
+    ("ceil", NativeFunction::Ceil),
+    ("round", NativeFunction::Round),
+    ("min", NativeFunction::Min),
+    ("max", NativeFunction::Max),
+    ("if", NativeFunction::If),
+    ("piecewise", NativeFunction::Piecewise),
+    ("sum", NativeFunction::Sum),
+    ("prod", NativeFunction::Product),
+    ("integrate", NativeFunction::Integrate),
+    ("solve", NativeFunction::Solve),
+    ("root", NativeFunction::Root),
+    ("roots", NativeFunction::Roots),
+    ("deriv", NativeFunction::Derivative),
+    ("limit", NativeFunction::Limit),
+    ("limit_left", NativeFunction::LimitLeft),
+    ("limit_right", NativeFunction::LimitRight),
+    ("len", NativeFunction::Len),
+    ("transpose", NativeFunction::Transpose),
+    ("det", NativeFunction::Determinant),
+    ("inv", NativeFunction::Inverse),
+    ("identity", NativeFunction::Identity),
+    ("trace", NativeFunction::Trace),
+    ("rank", NativeFunction::Rank),
+    ("eig", NativeFunction::Eigenvalues),
+    ("mean", NativeFunction::Mean),
+    ("median", NativeFunction::Median),
+    ("mode", NativeFunction::Mode),
+    ("var", NativeFunction::Variance),
+    ("varp", NativeFunction::PopulationVariance),
+    ("stdev", NativeFunction::StandardDeviation),
+    ("stdevp", NativeFunction::PopulationStandardDeviation),
+    ("quantile", NativeFunction::Quantile),
+    ("geomean", NativeFunction::GeometricMean),
+    ("harmean", NativeFunction::HarmonicMean),
+    ("skewness", NativeFunction::Skewness),
+    ("kurtosis", NativeFunction::Kurtosis),
+    ("gamma", NativeFunction::Gamma),
+    ("lgamma", NativeFunction::LnGamma),
+    ("digamma", NativeFunction::Digamma),
+    ("erf", NativeFunction::Erf),
+    ("erfc", NativeFunction::Erfc),
+    ("erfinv", NativeFunction::ErfInv),
+    ("zeta", NativeFunction::Zeta),
+    ("lambertw", NativeFunction::LambertW),
+    ("beta", NativeFunction::Beta),
+    ("besselj", NativeFunction::BesselJ),
+    ("bessely", NativeFunction::BesselY),
+    ("besseli", NativeFunction::BesselI),
+    ("besselk", NativeFunction::BesselK),
+    ("gammainc", NativeFunction::GammaInc),
+    ("gammaincc", NativeFunction::GammaIncC),
+    ("betainc", NativeFunction::BetaInc),
+    ("nCr", NativeFunction::Binomial),
+    ("ncr", NativeFunction::Binomial),
+    ("nPr", NativeFunction::Permutations),
+    ("npr", NativeFunction::Permutations),
+    ("gcd", NativeFunction::Gcd),
+    ("lcm", NativeFunction::Lcm),
+    ("isprime", NativeFunction::IsPrime),
+    ("nextprime", NativeFunction::NextPrime),
+    ("factor", NativeFunction::Factor),
+    ("totient", NativeFunction::Totient),
+    ("mod_pow", NativeFunction::ModPow),
+    ("mod_inv", NativeFunction::ModInv),
+    ("fib", NativeFunction::Fibonacci),
+    ("subfactorial", NativeFunction::Subfactorial),
+    ("rand", NativeFunction::Rand),
+    ("randint", NativeFunction::RandInt),
+    ("randn", NativeFunction::Sample(Distribution::Normal)),
+    ("choice", NativeFunction::Choice),
+    ("npv", NativeFunction::Financial(Financial::NetPresentValue)),
+    (
+        "irr",
+        NativeFunction::Financial(Financial::InternalRateOfReturn),
+    ),
+    ("pmt", NativeFunction::Financial(Financial::Payment)),
+    ("fv", NativeFunction::Financial(Financial::FutureValue)),
+    ("pv", NativeFunction::Financial(Financial::PresentValue)),
+    ("nper", NativeFunction::Financial(Financial::Periods)),
+    ("rate", NativeFunction::Financial(Financial::Rate)),
+    ("amortize", NativeFunction::Financial(Financial::Amortize)),
+    (
+        "normpdf",
+        NativeFunction::Distribution(Distribution::Normal, DistributionFunction::Density),
+    ),
+    (
+        "normcdf",
+        NativeFunction::Distribution(Distribution::Normal, DistributionFunction::Cumulative),
+    ),
+    (
+        "norminv",
+        NativeFunction::Distribution(Distribution::Normal, DistributionFunction::Inverse),
+    ),
+    (
+        "tpdf",
+        NativeFunction::Distribution(Distribution::StudentsT, DistributionFunction::Density),
+    ),
+    (
+        "tcdf",
+        NativeFunction::Distribution(Distribution::StudentsT, DistributionFunction::Cumulative),
+    ),
+    (
+        "tinv",
+        NativeFunction::Distribution(Distribution::StudentsT, DistributionFunction::Inverse),
+    ),
+    (
+        "chi2pdf",
+        NativeFunction::Distribution(Distribution::ChiSquared, DistributionFunction::Density),
+    ),
+    (
+        "chi2cdf",
+        NativeFunction::Distribution(Distribution::ChiSquared, DistributionFunction::Cumulative),
+    ),
+    (
+        "chi2inv",
+        NativeFunction::Distribution(Distribution::ChiSquared, DistributionFunction::Inverse),
+    ),
+    (
+        "fpdf",
+        NativeFunction::Distribution(Distribution::FisherSnedecor, DistributionFunction::Density),
+    ),
+    (
+        "fcdf",
+        NativeFunction::Distribution(
+            Distribution::FisherSnedecor,
+            DistributionFunction::Cumulative,
+        ),
+    ),
+    (
+        "finv",
+        NativeFunction::Distribution(Distribution::FisherSnedecor, DistributionFunction::Inverse),
+    ),
+    (
+        "exppdf",
+        NativeFunction::Distribution(Distribution::Exponential, DistributionFunction::Density),
+    ),
+    (
+        "expcdf",
+        NativeFunction::Distribution(Distribution::Exponential, DistributionFunction::Cumulative),
+    ),
+    (
+        "expinv",
+        NativeFunction::Distribution(Distribution::Exponential, DistributionFunction::Inverse),
+    ),
+    (
+        "betapdf",
+        NativeFunction::Distribution(Distribution::Beta, DistributionFunction::Density),
+    ),
+    (
+        "betacdf",
+        NativeFunction::Distribution(Distribution::Beta, DistributionFunction::Cumulative),
+    ),
+    (
+        "betainv",
+        NativeFunction::Distribution(Distribution::Beta, DistributionFunction::Inverse),
+    ),
+    (
+        "gammapdf",
+        NativeFunction::Distribution(Distribution::Gamma, DistributionFunction::Density),
+    ),
+    (
+        "gammacdf",
+        NativeFunction::Distribution(Distribution::Gamma, DistributionFunction::Cumulative),
+    ),
+    (
+        "gammainv",
+        NativeFunction::Distribution(Distribution::Gamma, DistributionFunction::Inverse),
+    ),
+    (
+        "binompdf",
+        NativeFunction::Distribution(Distribution::Binomial, DistributionFunction::Density),
+    ),
+    (
+        "binomcdf",
+        NativeFunction::Distribution(Distribution::Binomial, DistributionFunction::Cumulative),
+    ),
+    (
+        "poissonpdf",
+        NativeFunction::Distribution(Distribution::Poisson, DistributionFunction::Density),
+    ),
+    (
+        "poissoncdf",
+        NativeFunction::Distribution(Distribution::Poisson, DistributionFunction::Cumulative),
+    ),
+    ("normrnd", NativeFunction::Sample(Distribution::Normal)),
+    ("trnd", NativeFunction::Sample(Distribution::StudentsT)),
+    ("chi2rnd", NativeFunction::Sample(Distribution::ChiSquared)),
+    ("frnd", NativeFunction::Sample(Distribution::FisherSnedecor)),
+    ("exprnd", NativeFunction::Sample(Distribution::Exponential)),
+    ("betarnd", NativeFunction::Sample(Distribution::Beta)),
+    ("gammarnd", NativeFunction::Sample(Distribution::Gamma)),
+    ("binomrnd", NativeFunction::Sample(Distribution::Binomial)),
+    ("poissonrnd", NativeFunction::Sample(Distribution::Poisson)),
+];
+
+impl NativeFunction {
+    pub fn from_name(name: &str) -> Option<NativeFunction> {
+        FUNCTIONS
+            .iter()
+            .find(|(function_name, _)| *function_name == name)
+            .map(|(_, function)| function.clone())
+    }
+    /// The first name the function is known by.
+    pub fn name(&self) -> &'static str {
+        FUNCTIONS
+            .iter()
+            .find(|(_, function)| function == self)
+            .map_or("", |(name, _)| name)
+    }
 }
 
 #[derive(Debug, PartialEq, Clone)]
@@ -35,49 +347,99 @@ pub enum Token {
     Subtract,
     Multiply,
     Divide,
+    /// `a \ b`, solving `a x = b` for `x`.
+    Backslash,
     Caret,
     ExclamationMark,
+    /// `!!`, the double factorial.
+    DoubleExclamationMark,
     Modulo,
+    IntegerDivide,
+    Ampersand,
+    Xor,
+    Tilde,
+    ShiftLeft,
+    ShiftRight,
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+    Equal,
+    NotEqual,
+    /// A single `=`, separating both sides of an equation.
+    EqualSign,
+    And,
+    Or,
+    Not,
     LeftParen,
     RightParen,
+    LeftBracket,
+    RightBracket,
+    Range,
     LeftFloor,
     RightFloor,
     LeftCeiling,
     RightCeiling,
     Pow2,
     Pow3,
-    E,
-    Pi,
     Comma,
+    Question,
+    Colon,
     Bar,
     DegToRad,
     RadToDeg,
     ExplicitFunction(NativeFunction),
     Num(f64),
+    Boolean(bool),
+    Identifier(String),
     Ans,
     Eof,
 }
 
-#[derive(Debug, PartialEq, PartialOrd, Clone)]
+/// Precedence levels, from the loosest to the tightest. Which operator is at which level is
+/// given by `operators::OPERATORS`.
+#[derive(Debug, PartialEq, PartialOrd, Clone, Copy)]
 pub enum OperPrec {
     DefaultZero,
+    Conditional,
+    Or,
+    And,
+    Not,
+    Comparison,
+    Range,
+    BitOr,
+    BitXor,
+    BitAnd,
+    Shift,
     AddSub,
     MulDiv,
-    Power,
     Negative,
+    Power,
     Functional,
 }
 
-impl Token {
-    pub fn get_oper_prec(&self) -> OperPrec {
-        use self::OperPrec::*;
-        use self::Token::*;
-        match *self {
-            Add | Subtract => AddSub,
-            Multiply | Divide | Modulo | DegToRad | RadToDeg => MulDiv,
-            Caret | Pow2 | Pow3 => Power,
-            ExclamationMark | ExplicitFunction(_) => Functional,
-            _ => DefaultZero,
-        }
+impl OperPrec {
+    /// Every level in the order of the enum, which `looser` indexes by discriminant.
+    pub const LEVELS: [OperPrec; 16] = [
+        OperPrec::DefaultZero,
+        OperPrec::Conditional,
+        OperPrec::Or,
+        OperPrec::And,
+        OperPrec::Not,
+        OperPrec::Comparison,
+        OperPrec::Range,
+        OperPrec::BitOr,
+        OperPrec::BitXor,
+        OperPrec::BitAnd,
+        OperPrec::Shift,
+        OperPrec::AddSub,
+        OperPrec::MulDiv,
+        OperPrec::Negative,
+        OperPrec::Power,
+        OperPrec::Functional,
+    ];
+    /// The level right below this one, `DefaultZero` being its own.
+    pub fn looser(self) -> OperPrec {
+        OperPrec::LEVELS[(self as usize).saturating_sub(1)]
     }
 }
@@ -1,23 +1,163 @@
+use super::parser::ParseError;
 use super::token::{NativeFunction, Token};
-use std::iter::Peekable;
 use std::str::Chars;
 
 pub struct Tokenizer<'a> {
-    expr: Peekable<Chars<'a>>,
+    expr: Cursor<'a>,
+    length: usize,
+    token_offset: usize,
 }
 
 impl<'a> Tokenizer<'a> {
     pub fn new(new_expr: &'a str) -> Self {
         Tokenizer {
-            expr: new_expr.chars().peekable(),
+            expr: Cursor(new_expr.chars()),
+            length: new_expr.len(),
+            token_offset: 0,
         }
     }
+    /// Byte offset in the input of the token last read, or of the character it failed on.
+    pub fn token_offset(&self) -> usize {
+        self.token_offset
+    }
+}
+
+/// The characters left to read, like a `Peekable<Chars>` but telling how much of the input
+/// remains.
+#[derive(Clone)]
+struct Cursor<'a>(Chars<'a>);
+
+impl Cursor<'_> {
+    fn peek(&self) -> Option<char> {
+        self.0.clone().next()
+    }
+    fn next_if(&mut self, accept: impl FnOnce(&char) -> bool) -> Option<char> {
+        let mut lookahead = self.0.clone();
+        let next_char = lookahead.next().filter(accept)?;
+        self.0 = lookahead;
+        Some(next_char)
+    }
+    fn next_if_eq(&mut self, expected: &char) -> Option<char> {
+        self.next_if(|next_char| next_char == expected)
+    }
+    fn remaining(&self) -> usize {
+        self.0.as_str().len()
+    }
+}
+
+impl Iterator for Cursor<'_> {
+    type Item = char;
+
+    fn next(&mut self) -> Option<char> {
+        self.0.next()
+    }
+}
+
+impl<'a> Tokenizer<'a> {
+    /// Read a number literal, including an exponent such as `e-3`.
+    /// An `e` that isn't followed by digits is left alone since it's Euler's number.
+    fn read_number(&mut self) -> Result<Token, ParseError> {
+        let mut number = String::new();
+        if self.expr.peek() == Some('.') {
+            number.push('0');
+        }
+        loop {
+            let mut lookahead = self.expr.clone();
+            match lookahead.next() {
+                // `1..5` is a range, not a malformed number.
+                Some('.') if lookahead.peek() == Some('.') => break,
+                Some(next_char) if next_char.is_ascii_digit() || next_char == '.' => {
+                    number.push(next_char);
+                    self.expr.next();
+                }
+                _ => break,
+            }
+        }
+        let mut exponent = self.expr.clone();
+        if let Some(e @ ('e' | 'E')) = exponent.next() {
+            let mut suffix = e.to_string();
+            if let Some(sign) = exponent.next_if(|c| *c == '+' || *c == '-') {
+                suffix.push(sign);
+            }
+            if exponent.peek().is_some_and(|c| c.is_ascii_digit()) {
+                while let Some(digit) = exponent.next_if(|c| c.is_ascii_digit()) {
+                    suffix.push(digit);
+                }
+                number.push_str(&suffix);
+                self.expr = exponent;
+            }
+        }
+        match number.parse::<f64>() {
+            Ok(value) => Ok(Token::Num(value)),
+            Err(_) => Err(ParseError::MalformedNumber(number)),
+        }
+    }
+    /// Read a whole word, which is either a keyword, a function name or a variable.
+    fn read_word(&mut self) -> Token {
+        let mut word = String::new();
+        while let Some(next_char) = self
+            .expr
+            .next_if(|c| c.is_alphabetic() || c.is_ascii_digit() || *c == '_')
+        {
+            word.push(next_char);
+        }
+        match keyword(&word) {
+            Some(token) => token,
+            None => match NativeFunction::from_name(&word) {
+                Some(function) => Token::ExplicitFunction(function),
+                None => Token::Identifier(word),
+            },
+        }
+    }
+}
+
+fn keyword(word: &str) -> Option<Token> {
+    let token = match word {
+        // Constants are looked up by name, this one is only spelled differently.
+        "π" => Token::Identifier("pi".to_string()),
+        "inf" => Token::Num(f64::INFINITY),
+        "nan" | "NaN" => Token::Num(f64::NAN),
+        "rad" => Token::RadToDeg,
+        "xor" => Token::Xor,
+        "and" => Token::And,
+        "or" => Token::Or,
+        "not" => Token::Not,
+        "true" => Token::Boolean(true),
+        "false" => Token::Boolean(false),
+        _ => return None,
+    };
+    Some(token)
 }
 
 impl<'a> Iterator for Tokenizer<'a> {
-    type Item = Token;
+    type Item = Result<Token, ParseError>;
 
-    fn next(&mut self) -> Option<Token> {
+    fn next(&mut self) -> Option<Result<Token, ParseError>> {
+        while self.expr.next_if(|c| c.is_whitespace()).is_some() {}
+        self.token_offset = self.length - self.expr.remaining();
+        let current_char = match self.expr.peek() {
+            Some(current_char) => current_char,
+            None => return Some(Ok(Token::Eof)),
+        };
+        let mut lookahead = self.expr.clone();
+        lookahead.next();
+        let starts_number = current_char.is_ascii_digit()
+            || (current_char == '.' && lookahead.peek().is_some_and(|c| c.is_ascii_digit()));
+        if starts_number {
+            Some(self.read_number())
+        } else if current_char.is_alphabetic() || current_char == '_' {
+            Some(Ok(self.read_word()))
+        } else {
+            Some(
+                self.read_symbol()
+                    .ok_or(ParseError::UnknownCharacter(current_char)),
+            )
+        }
+    }
+}
+
+impl<'a> Tokenizer<'a> {
+    fn read_symbol(&mut self) -> Option<Token> {
         let current_char = self.expr.next();
 
         match current_char {
@@ -25,205 +165,73 @@ impl<'a> Iterator for Tokenizer<'a> {
             Some('+') => Some(Token::Add),
             Some('-') => Some(Token::Subtract),
             Some('*') => Some(Token::Multiply),
-            Some('/') => Some(Token::Divide),
-            Some('^') => Some(Token::Caret),
-            Some('(') => Some(Token::LeftParen),
-            Some(')') => Some(Token::RightParen),
-            Some('|') => Some(Token::Bar),
-            Some('!') => Some(Token::ExclamationMark),
-            Some(',') => Some(Token::Comma),
-            Some('%') => Some(Token::Modulo),
-            Some('π') => Some(Token::Pi),
-            Some('⌊') => Some(Token::LeftFloor),
-            Some('⌋') => Some(Token::RightFloor),
-            Some('⌈') => Some(Token::LeftCeiling),
-            Some('⌉') => Some(Token::RightCeiling),
-            Some('²') => Some(Token::Pow2),
-            Some('³') => Some(Token::Pow3),
-            Some('°') => Some(Token::DegToRad),
-            Some('.') => {
-                let next_char = self.expr.peek()?;
-                if next_char.is_ascii_digit() {
-                    let mut number = "0".to_string();
-                    number.push(current_char?);
-                    while let Some(next_char) = self.expr.peek() {
-                        if next_char.is_ascii_digit() || next_char == &'.' {
-                            number.push(self.expr.next()?);
-                        } else {
-                            break;
-                        }
-                    }
-                    Some(Token::Num(number.parse::<f64>().unwrap()))
-                } else {
-                    None
-                }
-            }
-            Some('0'..='9') => {
-                let mut number = current_char?.to_string();
-                while let Some(next_char) = self.expr.peek() {
-                    if next_char.is_ascii_digit() || next_char == &'.' {
-                        number.push(self.expr.next()?);
-                    } else {
-                        break;
-                    }
-                }
-                Some(Token::Num(number.parse::<f64>().unwrap()))
-            }
-            Some('a') => {
-                if self.expr.clone().take(5).collect::<String>() == "tan2(" {
-                    self.expr.by_ref().take(4).for_each(drop);
-                    Some(Token::ExplicitFunction(NativeFunction::Atan2))
-                } else if self.expr.clone().take(4).collect::<String>() == "sin(" {
-                    self.expr.by_ref().take(3).for_each(drop);
-                    Some(Token::ExplicitFunction(NativeFunction::Asin))
-                } else if self.expr.clone().take(4).collect::<String>() == "cos(" {
-                    self.expr.by_ref().take(3).for_each(drop);
-                    Some(Token::ExplicitFunction(NativeFunction::Acos))
-                } else if self.expr.clone().take(4).collect::<String>() == "tan(" {
-                    self.expr.by_ref().take(3).for_each(drop);
-                    Some(Token::ExplicitFunction(NativeFunction::Atan))
-                } else if self.expr.clone().take(4).collect::<String>() == "rsinh(" {
-                    self.expr.by_ref().take(3).for_each(drop);
-                    Some(Token::ExplicitFunction(NativeFunction::Arsinh))
-                } else if self.expr.clone().take(4).collect::<String>() == "rcosh(" {
-                    self.expr.by_ref().take(3).for_each(drop);
-                    Some(Token::ExplicitFunction(NativeFunction::Arcosh))
-                } else if self.expr.clone().take(4).collect::<String>() == "rtanh(" {
-                    self.expr.by_ref().take(3).for_each(drop);
-                    Some(Token::ExplicitFunction(NativeFunction::Artanh))
-                } else if self.expr.clone().take(4).collect::<String>() == "sinh(" {
-                    self.expr.by_ref().take(3).for_each(drop);
-                    Some(Token::ExplicitFunction(NativeFunction::Arsinh))
-                } else if self.expr.clone().take(4).collect::<String>() == "cosh(" {
-                    self.expr.by_ref().take(3).for_each(drop);
-                    Some(Token::ExplicitFunction(NativeFunction::Arcosh))
-                } else if self.expr.clone().take(4).collect::<String>() == "tanh(" {
-                    self.expr.by_ref().take(3).for_each(drop);
-                    Some(Token::ExplicitFunction(NativeFunction::Artanh))
-                } else if self.expr.clone().take(3).collect::<String>() == "bs(" {
-                    self.expr.by_ref().take(2).for_each(drop);
-                    Some(Token::ExplicitFunction(NativeFunction::Abs))
-                } else {
-                    None
-                }
-            }
-            Some('c') => {
-                if self.expr.clone().take(3).collect::<String>() == "os(" {
-                    self.expr.by_ref().take(2).for_each(drop);
-                    Some(Token::ExplicitFunction(NativeFunction::Cos))
-                } else if self.expr.clone().take(4).collect::<String>() == "osh(" {
-                    self.expr.by_ref().take(3).for_each(drop);
-                    Some(Token::ExplicitFunction(NativeFunction::Cosh))
-                } else if self.expr.clone().take(4).collect::<String>() == "eil(" {
-                    self.expr.by_ref().take(3).for_each(drop);
-                    Some(Token::ExplicitFunction(NativeFunction::Ceil))
-                } else {
-                    None
-                }
-            }
-            Some('f') => {
-                if self.expr.clone().take(5).collect::<String>() == "loor(" {
-                    self.expr.by_ref().take(4).for_each(drop);
-                    Some(Token::ExplicitFunction(NativeFunction::Floor))
-                } else {
-                    None
-                }
-            }
-            Some('e') => {
-                if self.expr.clone().take(3).collect::<String>() == "xp(" {
-                    self.expr.by_ref().take(2).for_each(drop);
-                    Some(Token::ExplicitFunction(NativeFunction::Exp))
-                } else if self.expr.clone().take(4).collect::<String>() == "xp2(" {
-                    self.expr.by_ref().take(3).for_each(drop);
-                    Some(Token::ExplicitFunction(NativeFunction::Exp2))
-                } else {
-                    Some(Token::E)
-                }
-            }
-            Some('l') => {
-                if self.expr.clone().take(2).collect::<String>() == "n(" {
-                    self.expr.by_ref().take(1).for_each(drop);
-                    Some(Token::ExplicitFunction(NativeFunction::Ln))
-                } else if self.expr.clone().take(3).collect::<String>() == "og(" {
-                    self.expr.by_ref().take(2).for_each(drop);
-                    Some(Token::ExplicitFunction(NativeFunction::Log))
-                } else {
-                    None
-                }
-            }
-            Some('m') => {
-                if self.expr.clone().take(3).collect::<String>() == "in(" {
-                    self.expr.by_ref().take(2).for_each(drop);
-                    Some(Token::ExplicitFunction(NativeFunction::Min))
-                } else if self.expr.clone().take(3).collect::<String>() == "ax(" {
-                    self.expr.by_ref().take(2).for_each(drop);
-                    Some(Token::ExplicitFunction(NativeFunction::Max))
+            Some('/') => {
+                if self.expr.peek() == Some('/') {
+                    self.expr.next();
+                    Some(Token::IntegerDivide)
                 } else {
-                    None
+                    Some(Token::Divide)
                 }
             }
-            Some('p') => {
-                if self.expr.clone().take(1).collect::<String>() == "i" {
-                    self.expr.by_ref().take(1).for_each(drop);
-                    Some(Token::Pi)
-                } else if self.expr.clone().take(3).collect::<String>() == "ow(" {
-                    self.expr.by_ref().take(2).for_each(drop);
-                    Some(Token::ExplicitFunction(NativeFunction::Pow))
+            Some('\\') => Some(Token::Backslash),
+            Some('&') => Some(Token::Ampersand),
+            Some('[') => Some(Token::LeftBracket),
+            Some(']') => Some(Token::RightBracket),
+            Some('.') => self.expr.next_if_eq(&'.').map(|_| Token::Range),
+            Some('~') => Some(Token::Tilde),
+            Some('<') => {
+                if self.expr.next_if_eq(&'<').is_some() {
+                    Some(Token::ShiftLeft)
+                } else if self.expr.next_if_eq(&'=').is_some() {
+                    Some(Token::LessEqual)
                 } else {
-                    None
+                    Some(Token::Less)
                 }
             }
-            Some('r') => {
-                if self.expr.clone().take(5).collect::<String>() == "ound(" {
-                    self.expr.by_ref().take(4).for_each(drop);
-                    Some(Token::ExplicitFunction(NativeFunction::Round))
-                } else if self.expr.clone().take(2).collect::<String>() == "ad" {
-                    self.expr.by_ref().take(2).for_each(drop);
-                    Some(Token::RadToDeg)
+            Some('>') => {
+                if self.expr.next_if_eq(&'>').is_some() {
+                    Some(Token::ShiftRight)
+                } else if self.expr.next_if_eq(&'=').is_some() {
+                    Some(Token::GreaterEqual)
                 } else {
-                    None
+                    Some(Token::Greater)
                 }
             }
-            Some('s') => {
-                if self.expr.clone().take(3).collect::<String>() == "in(" {
-                    self.expr.by_ref().take(2).for_each(drop);
-                    Some(Token::ExplicitFunction(NativeFunction::Sin))
-                } else if self.expr.clone().take(4).collect::<String>() == "inh(" {
-                    self.expr.by_ref().take(3).for_each(drop);
-                    Some(Token::ExplicitFunction(NativeFunction::Sinh))
-                } else if self.expr.clone().take(4).collect::<String>() == "qrt(" {
-                    self.expr.by_ref().take(3).for_each(drop);
-                    Some(Token::ExplicitFunction(NativeFunction::Sqrt))
-                } else if self.expr.clone().take(4).collect::<String>() == "ign(" {
-                    self.expr.by_ref().take(3).for_each(drop);
-                    Some(Token::ExplicitFunction(NativeFunction::Sign))
-                } else if self.expr.clone().take(6).collect::<String>() == "ignum(" {
-                    self.expr.by_ref().take(5).for_each(drop);
-                    Some(Token::ExplicitFunction(NativeFunction::Sign))
-                } else if self.expr.clone().take(3).collect::<String>() == "gn(" {
-                    self.expr.by_ref().take(2).for_each(drop);
-                    Some(Token::ExplicitFunction(NativeFunction::Sign))
+            Some('=') => {
+                if self.expr.next_if_eq(&'=').is_some() {
+                    Some(Token::Equal)
                 } else {
-                    None
+                    Some(Token::EqualSign)
                 }
             }
-            Some('t') => {
-                if self.expr.clone().take(3).collect::<String>() == "an(" {
-                    self.expr.by_ref().take(2).for_each(drop);
-                    Some(Token::ExplicitFunction(NativeFunction::Tan))
-                } else if self.expr.clone().take(4).collect::<String>() == "anh(" {
-                    self.expr.by_ref().take(3).for_each(drop);
-                    Some(Token::ExplicitFunction(NativeFunction::Tanh))
-                } else if self.expr.clone().take(8).collect::<String>() == "runcate(" {
-                    self.expr.by_ref().take(7).for_each(drop);
-                    Some(Token::ExplicitFunction(NativeFunction::Truncate))
-                } else if self.expr.clone().take(5).collect::<String>() == "runc(" {
-                    self.expr.by_ref().take(4).for_each(drop);
-                    Some(Token::ExplicitFunction(NativeFunction::Truncate))
+            Some('≤') => Some(Token::LessEqual),
+            Some('≥') => Some(Token::GreaterEqual),
+            Some('≠') => Some(Token::NotEqual),
+            Some('^') => Some(Token::Caret),
+            Some('(') => Some(Token::LeftParen),
+            Some(')') => Some(Token::RightParen),
+            Some('|') => Some(Token::Bar),
+            Some('!') => {
+                if self.expr.next_if_eq(&'=').is_some() {
+                    Some(Token::NotEqual)
+                } else if self.expr.next_if_eq(&'!').is_some() {
+                    Some(Token::DoubleExclamationMark)
                 } else {
-                    None
+                    Some(Token::ExclamationMark)
                 }
             }
+            Some(',') => Some(Token::Comma),
+            Some('?') => Some(Token::Question),
+            Some(':') => Some(Token::Colon),
+            Some('%') => Some(Token::Modulo),
+            Some('⌊') => Some(Token::LeftFloor),
+            Some('⌋') => Some(Token::RightFloor),
+            Some('⌈') => Some(Token::LeftCeiling),
+            Some('⌉') => Some(Token::RightCeiling),
+            Some('²') => Some(Token::Pow2),
+            Some('³') => Some(Token::Pow3),
+            Some('°') => Some(Token::DegToRad),
+            Some('∞') => Some(Token::Num(f64::INFINITY)),
             None => Some(Token::Eof),
             Some(_) => None,
         }
@@ -237,23 +245,217 @@ mod tests {
     #[test]
     fn test_integer_number() {
         let mut tokenizer = Tokenizer::new("34");
-        assert_eq!(tokenizer.next().unwrap(), Token::Num(34.0))
+        assert_eq!(tokenizer.next().unwrap().unwrap(), Token::Num(34.0))
     }
     #[test]
     fn test_decimal_number() {
         let mut tokenizer = Tokenizer::new("34.5");
-        assert_eq!(tokenizer.next().unwrap(), Token::Num(34.5))
+        assert_eq!(tokenizer.next().unwrap().unwrap(), Token::Num(34.5))
     }
     #[test]
     fn test_decimal_number_omit_zero() {
         let mut tokenizer = Tokenizer::new(".5");
-        assert_eq!(tokenizer.next().unwrap(), Token::Num(0.5))
+        assert_eq!(tokenizer.next().unwrap().unwrap(), Token::Num(0.5))
+    }
+    #[test]
+    fn test_scientific_notation() {
+        assert_eq!(
+            Tokenizer::new("1.5e-3").next(),
+            Some(Ok(Token::Num(1.5e-3)))
+        );
+        assert_eq!(Tokenizer::new("2E+10").next(), Some(Ok(Token::Num(2e10))));
+        assert_eq!(Tokenizer::new(".5e2").next(), Some(Ok(Token::Num(50.0))));
+    }
+    #[test]
+    fn test_euler_after_number() {
+        let tokens = Tokenizer::new("2e-x")
+            .take(3)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(
+            tokens,
+            [
+                Token::Num(2.0),
+                Token::Identifier("e".to_string()),
+                Token::Subtract
+            ]
+        );
+    }
+    #[test]
+    fn test_malformed_number() {
+        assert_eq!(
+            Tokenizer::new("1.2.3").next(),
+            Some(Err(ParseError::MalformedNumber("1.2.3".to_string())))
+        );
+        assert_eq!(
+            Tokenizer::new("1.2.").next(),
+            Some(Err(ParseError::MalformedNumber("1.2.".to_string())))
+        );
+    }
+    #[test]
+    fn test_lists_and_ranges() {
+        let tokens = Tokenizer::new("[1.5, .5][1..2.]")
+            .take(10)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(
+            tokens,
+            [
+                Token::LeftBracket,
+                Token::Num(1.5),
+                Token::Comma,
+                Token::Num(0.5),
+                Token::RightBracket,
+                Token::LeftBracket,
+                Token::Num(1.0),
+                Token::Range,
+                Token::Num(2.0),
+                Token::RightBracket,
+            ]
+        );
+    }
+    #[test]
+    fn test_factorials() {
+        let tokens = Tokenizer::new("5!!!=1")
+            .take(5)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(
+            tokens,
+            [
+                Token::Num(5.0),
+                Token::DoubleExclamationMark,
+                Token::NotEqual,
+                Token::Num(1.0),
+                Token::Eof,
+            ]
+        );
+    }
+    #[test]
+    fn test_unknown_character() {
+        let mut tokenizer = Tokenizer::new("1$");
+        tokenizer.next();
+        assert_eq!(
+            tokenizer.next(),
+            Some(Err(ParseError::UnknownCharacter('$')))
+        );
+    }
+    #[test]
+    fn test_comparison_operators() {
+        let tokens = Tokenizer::new("1<2<=3==4!=5>=6>7≠8<<9")
+            .take(16)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(
+            tokens,
+            [
+                Token::Num(1.0),
+                Token::Less,
+                Token::Num(2.0),
+                Token::LessEqual,
+                Token::Num(3.0),
+                Token::Equal,
+                Token::Num(4.0),
+                Token::NotEqual,
+                Token::Num(5.0),
+                Token::GreaterEqual,
+                Token::Num(6.0),
+                Token::Greater,
+                Token::Num(7.0),
+                Token::NotEqual,
+                Token::Num(8.0),
+                Token::ShiftLeft,
+            ]
+        )
+    }
+    #[test]
+    fn test_bitwise_operators() {
+        let tokens = Tokenizer::new("1&2 xor 3<<4>>5//6~7")
+            .take(12)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(
+            tokens,
+            [
+                Token::Num(1.0),
+                Token::Ampersand,
+                Token::Num(2.0),
+                Token::Xor,
+                Token::Num(3.0),
+                Token::ShiftLeft,
+                Token::Num(4.0),
+                Token::ShiftRight,
+                Token::Num(5.0),
+                Token::IntegerDivide,
+                Token::Num(6.0),
+                Token::Tilde,
+            ]
+        )
+    }
+    #[test]
+    fn test_words() {
+        let tokens = Tokenizer::new("2k_1 + asinh(x²) rad")
+            .take(9)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(
+            tokens,
+            [
+                Token::Num(2.0),
+                Token::Identifier("k_1".to_string()),
+                Token::Add,
+                Token::ExplicitFunction(NativeFunction::Arsinh),
+                Token::LeftParen,
+                Token::Identifier("x".to_string()),
+                Token::Pow2,
+                Token::RightParen,
+                Token::RadToDeg,
+            ]
+        )
+    }
+    #[test]
+    fn test_word_operators() {
+        let tokens = Tokenizer::new("order or x_1 and not_1 xor2")
+            .take(7)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(
+            tokens,
+            [
+                Token::Identifier("order".to_string()),
+                Token::Or,
+                Token::Identifier("x_1".to_string()),
+                Token::And,
+                Token::Identifier("not_1".to_string()),
+                Token::Identifier("xor2".to_string()),
+                Token::Eof,
+            ]
+        )
+    }
+    #[test]
+    fn test_special_numbers() {
+        let tokens = Tokenizer::new("inf ∞ infinity")
+            .take(3)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(
+            tokens,
+            [
+                Token::Num(f64::INFINITY),
+                Token::Num(f64::INFINITY),
+                Token::Identifier("infinity".to_string()),
+            ]
+        );
+        for nan in ["nan", "NaN"] {
+            let token = Tokenizer::new(nan).next().unwrap().unwrap();
+            assert!(matches!(token, Token::Num(x) if x.is_nan()));
+        }
     }
     #[test]
     fn test_sin_function() {
         let mut tokenizer = Tokenizer::new("sin(3.14159)");
         assert_eq!(
-            tokenizer.next().unwrap(),
+            tokenizer.next().unwrap().unwrap(),
             Token::ExplicitFunction(NativeFunction::Sin)
         )
     }
@@ -261,7 +463,7 @@ mod tests {
     fn test_cos_function() {
         let mut tokenizer = Tokenizer::new("cos(3.14159)");
         assert_eq!(
-            tokenizer.next().unwrap(),
+            tokenizer.next().unwrap().unwrap(),
             Token::ExplicitFunction(NativeFunction::Cos)
         )
     }
@@ -269,7 +471,7 @@ mod tests {
     fn test_tan_function() {
         let mut tokenizer = Tokenizer::new("tan(.14159)");
         assert_eq!(
-            tokenizer.next().unwrap(),
+            tokenizer.next().unwrap().unwrap(),
             Token::ExplicitFunction(NativeFunction::Tan)
         )
     }
@@ -277,7 +479,7 @@ mod tests {
     fn test_atan2_function() {
         let mut tokenizer = Tokenizer::new("atan2(.14159, 2.1415)");
         assert_eq!(
-            tokenizer.next().unwrap(),
+            tokenizer.next().unwrap().unwrap(),
             Token::ExplicitFunction(NativeFunction::Atan2)
         )
     }
@@ -0,0 +1,139 @@
+use super::error::EvalError;
+use super::matrix::Matrix;
+use num_complex::Complex64;
+use std::fmt;
+
+/// Result of evaluating an expression.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Number(f64),
+    Boolean(bool),
+    Complex(Complex64),
+    List(Vec<Value>),
+    Matrix(Matrix),
+}
+
+impl Value {
+    /// A list of `items`, or a matrix when they're all lists of numbers of the same length.
+    pub fn from_items(items: Vec<Value>) -> Self {
+        let rows = items
+            .iter()
+            .map(|item| match item {
+                Value::List(row) => row
+                    .iter()
+                    .map(|element| match element {
+                        Value::Number(number) => Some(*number),
+                        _ => None,
+                    })
+                    .collect(),
+                _ => None,
+            })
+            .collect::<Option<Vec<Vec<f64>>>>();
+        match rows.and_then(Matrix::from_rows) {
+            Some(matrix) => Value::Matrix(matrix),
+            None => Value::List(items),
+        }
+    }
+    /// The elements of a list, or the rows of a matrix as lists.
+    pub fn items(&self) -> Option<Vec<Value>> {
+        match self {
+            Value::List(items) => Some(items.clone()),
+            Value::Matrix(matrix) => Some(
+                (0..matrix.rows())
+                    .map(|i| Value::List(matrix.row(i).iter().map(|x| Value::Number(*x)).collect()))
+                    .collect(),
+            ),
+            _ => None,
+        }
+    }
+    pub fn as_number(&self) -> Result<f64, EvalError> {
+        match self {
+            Value::Number(number) => Ok(*number),
+            _ => Err(EvalError::TypeMismatch {
+                expected: "a number",
+                got: self.to_string(),
+            }),
+        }
+    }
+    /// The numbers of a list.
+    pub fn as_numbers(&self) -> Result<Vec<f64>, EvalError> {
+        match self {
+            Value::List(items) => items.iter().map(Value::as_number).collect(),
+            _ => Err(EvalError::TypeMismatch {
+                expected: "a list of numbers",
+                got: self.to_string(),
+            }),
+        }
+    }
+    pub fn as_matrix(&self) -> Result<&Matrix, EvalError> {
+        match self {
+            Value::Matrix(matrix) => Ok(matrix),
+            _ => Err(EvalError::TypeMismatch {
+                expected: "a matrix",
+                got: self.to_string(),
+            }),
+        }
+    }
+    pub fn as_boolean(&self) -> Result<bool, EvalError> {
+        match self {
+            Value::Boolean(boolean) => Ok(*boolean),
+            _ => Err(EvalError::TypeMismatch {
+                expected: "a boolean",
+                got: self.to_string(),
+            }),
+        }
+    }
+}
+
+impl From<f64> for Value {
+    fn from(number: f64) -> Self {
+        Value::Number(number)
+    }
+}
+
+impl From<bool> for Value {
+    fn from(boolean: bool) -> Self {
+        Value::Boolean(boolean)
+    }
+}
+
+impl PartialEq<f64> for Value {
+    fn eq(&self, other: &f64) -> bool {
+        matches!(self, Value::Number(number) if number == other)
+    }
+}
+
+impl PartialEq<bool> for Value {
+    fn eq(&self, other: &bool) -> bool {
+        matches!(self, Value::Boolean(boolean) if boolean == other)
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Value::Number(number) => write!(f, "{:?}", number),
+            Value::Boolean(boolean) => write!(f, "{}", boolean),
+            Value::Complex(complex) if complex.im < 0.0 => {
+                write!(f, "{:?} - {:?}i", complex.re, -complex.im)
+            }
+            Value::Complex(complex) => write!(f, "{:?} + {:?}i", complex.re, complex.im),
+            Value::List(values) => {
+                write!(f, "[")?;
+                for (index, value) in values.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", value)?;
+                }
+                write!(f, "]")
+            }
+            Value::Matrix(matrix) => write!(f, "{}", matrix),
+        }
+    }
+}
+
+/// Whether two numbers are equal up to `tolerance`, relative to their magnitude once it's above 1.
+pub fn approx_eq(a: f64, b: f64, tolerance: f64) -> bool {
+    a == b || (a - b).abs() <= tolerance * a.abs().max(b.abs()).max(1.0)
+}
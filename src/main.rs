@@ -1,46 +1,218 @@
-mod calcparse;
-
-use calcparse::ast;
-use calcparse::parser::{ParseError, Parser};
+use calculator::calcparse::ast;
+use calculator::calcparse::constants;
+use calculator::calcparse::context::{Context, FloatPolicy};
+use calculator::calcparse::error::EvalError;
+use calculator::calcparse::finance;
+use calculator::calcparse::parser::{Diagnostic, Parser, MAX_ALLOWED_DEPTH};
+use calculator::calcparse::programmer::{IntegerMode, Overflow};
+use calculator::calcparse::value::Value;
 use std::io;
 
-fn evaluate(expr: String, old_answer: f64, debug: bool) -> Result<f64, ParseError> {
-    let expr = expr.split_whitespace().collect::<String>();
-    let mut math_parser = Parser::new(&expr, Some(old_answer))?;
-    let ast = math_parser.parse()?;
+/// Either the input isn't a valid expression, or its value is undefined.
+enum Failure {
+    /// Every error found in the input.
+    Syntax(Vec<Diagnostic>),
+    Math(EvalError),
+}
+
+fn evaluate(
+    expr: &str,
+    old_answer: Value,
+    debug: bool,
+    context: &mut Context,
+) -> Result<Value, Failure> {
+    let mut math_parser =
+        Parser::recovering(expr, Some(old_answer)).with_max_depth(context.max_depth);
+    let (ast, errors) = math_parser.parse_with_diagnostics();
+    if !errors.is_empty() {
+        return Err(Failure::Syntax(errors));
+    }
     if debug {
         println!("{:?}", ast);
     }
-    Ok(ast::eval(ast)?)
+    ast::eval_with(ast, context).map_err(Failure::Math)
+}
+
+fn run_command(command: &str, context: &mut Context) {
+    let args = command.split_whitespace().collect::<Vec<_>>();
+    match args[..] {
+        ["prog", "off"] => {
+            context.integer_mode = None;
+            println!("Programmer mode is now disabled");
+        }
+        ["prog", width] | ["prog", width, "checked"] => {
+            set_integer_mode(context, width, Overflow::Checked)
+        }
+        ["prog", width, "wrap"] => set_integer_mode(context, width, Overflow::Wrapping),
+        ["depth", depth] => match depth.parse() {
+            Ok(depth) if depth > MAX_ALLOWED_DEPTH => println!(
+                "Invalid nesting limit: {}, it can be at most {}",
+                depth, MAX_ALLOWED_DEPTH
+            ),
+            Ok(depth) => {
+                context.max_depth = depth;
+                println!("Nesting limit is now set to: {}", depth);
+            }
+            Err(_) => println!("Invalid nesting limit: {}", depth),
+        },
+        ["precision", precision] => match precision.parse::<f64>() {
+            Ok(precision) if precision > 0.0 => {
+                context.precision = precision;
+                println!("Numerical precision is now set to: {:?}", precision);
+            }
+            _ => println!("Invalid precision: {}", precision),
+        },
+        ["tolerance", tolerance] => match tolerance.parse::<f64>() {
+            Ok(tolerance) if tolerance >= 0.0 => {
+                context.tolerance = tolerance;
+                println!("Comparison tolerance is now set to: {:?}", tolerance);
+            }
+            _ => println!("Invalid tolerance: {}", tolerance),
+        },
+        ["policy", "ieee"] => {
+            context.float_policy = FloatPolicy::Ieee;
+            println!("Infinite and undefined results are now allowed");
+        }
+        ["policy", "strict"] => {
+            context.float_policy = FloatPolicy::Strict;
+            println!("Infinite and undefined results are now errors");
+        }
+        ["seed", seed] => match seed.parse() {
+            Ok(seed) => {
+                context.set_seed(seed);
+                println!("Random seed is now set to: {}", seed);
+            }
+            Err(_) => println!("Invalid seed: {}", seed),
+        },
+        ["amortize", rate, periods, present_value] => {
+            print_amortization(&[rate, periods, present_value], context)
+        }
+        ["consts"] => print_constants(),
+        _ => println!(
+            "Unknown command: {}\nAvailable commands: :prog <i8|u8|i16|u16|i32|u32|i64|u64> [wrap|checked], :prog off, :depth <n>, :tolerance <x>, :precision <x>, :policy <ieee|strict>, :seed <n>, :amortize <rate> <n> <pv>, :consts",
+            command
+        ),
+    }
+}
+
+/// Print the repayment schedule of a loan, each argument being an expression such as `0.05/12`.
+fn print_amortization(args: &[&str], context: &mut Context) {
+    let mut numbers = Vec::new();
+    for arg in args {
+        let number = Parser::new(arg, None)
+            .and_then(|mut parser| parser.parse())
+            .map_err(|err| err.to_string())
+            .and_then(|ast| ast::eval_with(ast, context).map_err(|err| err.to_string()))
+            .and_then(|value| value.as_number().map_err(|err| err.to_string()));
+        match number {
+            Ok(number) => numbers.push(number),
+            Err(err) => return println!("Invalid argument {}: {}", arg, err),
+        }
+    }
+    let schedule = match finance::amortize(numbers[0], numbers[1], numbers[2]) {
+        Ok(schedule) => schedule,
+        Err(err) => return println!("{}", err),
+    };
+    let [period, columns @ ..] = finance::AMORTIZATION_COLUMNS;
+    print!("{:>6}", period);
+    for column in columns {
+        print!("{:>14}", column);
+    }
+    println!();
+    for i in 0..schedule.rows() {
+        let [period, amounts @ ..] = schedule.row(i) else {
+            continue;
+        };
+        print!("{:>6}", period);
+        for amount in amounts {
+            print!("{:>14.2}", amount);
+        }
+        println!();
+    }
+}
+
+fn print_constants() {
+    for constant in constants::CONSTANTS {
+        println!(
+            "{:<10}{:<24}{:<16}{}",
+            constant.name,
+            format!("{:?}", constant.value),
+            constant.unit,
+            constant.description
+        );
+    }
+}
+
+fn set_integer_mode(context: &mut Context, width: &str, overflow: Overflow) {
+    match IntegerMode::from_width(width, overflow) {
+        Some(mode) => {
+            context.integer_mode = Some(mode);
+            println!("Programmer mode is now set to: {}", mode);
+        }
+        None => println!("Unknown integer width: {}", width),
+    }
 }
 
 fn main() {
     println!("Calculator started...");
-    let mut old_eval = 0.0;
+    let mut old_eval = Value::Number(0.0);
     let mut debug = false;
+    let mut context = Context::default();
     loop {
         let mut input = String::new();
         match io::stdin().read_line(&mut input) {
             Ok(_) => {
-                let cleaned_input = input.split_whitespace().collect::<String>();
-                if (cleaned_input == "exit") || (cleaned_input == "close") {
+                if let Some(command) = input.trim().strip_prefix(':') {
+                    run_command(command, &mut context);
+                    continue;
+                }
+                let command = input.trim();
+                if (command == "exit") || (command == "close") {
                     break;
-                } else if cleaned_input == "debug" {
+                } else if command == "debug" {
                     debug = !debug;
                     println!("Debugging is now set to: {:?}", debug);
                     continue;
                 }
-                match evaluate(cleaned_input, old_eval, debug) {
+                // Leading whitespace is kept so that the offsets of syntax errors are those of the
+                // line as typed.
+                match evaluate(input.trim_end(), old_eval.clone(), debug, &mut context) {
                     Ok(val) => {
+                        match context.estimated_error {
+                            Some(error) => println!("= {} (estimated error {:.1e})", val, error),
+                            None => println!("= {}", val),
+                        }
                         old_eval = val;
-                        println!("= {:?}", val)
                     }
-                    Err(err) => {
-                        println!("{:?}\nPlease enter valid expression.", err);
+                    Err(Failure::Syntax(errors)) => {
+                        for diagnostic in errors {
+                            println!("{}", diagnostic);
+                        }
+                        println!("Please enter valid expression.");
                     }
+                    Err(Failure::Math(err)) => println!("Math error: {}", err),
                 }
             }
             Err(error) => println!("error: {}", error),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn syntax_offsets(input: &str) -> Vec<usize> {
+        match evaluate(input, Value::Number(0.0), false, &mut Context::default()) {
+            Err(Failure::Syntax(errors)) => errors.iter().map(|error| error.offset).collect(),
+            _ => panic!("{} should be a syntax error", input),
+        }
+    }
+
+    #[test]
+    fn test_offsets_of_raw_input() {
+        assert_eq!(syntax_offsets("1 +   )"), [6]);
+        assert_eq!(syntax_offsets("  1 +   )"), [8]);
+        assert_eq!(syntax_offsets("sin(1) + 1 2"), [11]);
+    }
+}